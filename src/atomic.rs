@@ -0,0 +1,251 @@
+//! A counter shared across threads.
+//!
+//! [`AtomicCounter`] stores a [`Counter`] in an `AtomicU32`. Each update is a
+//! single atomic read-modify-write that returns the value it replaced, and
+//! the value it stored is that old value passed through the pure function of
+//! the same name: `fetch_add(n)` stores exactly `add(old, n)`. Every update
+//! goes through `AtomicU32::fetch_update` with that function, so the
+//! semantics match the crate root, including at `Counter::MAX`.
+//!
+//! Atomics are not extracted, so this module is excluded; the pure
+//! functions remain the verified source of truth.
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::Counter;
+use hax_lib as hax;
+
+/// A counter that can be updated concurrently through `&self`.
+///
+/// Every update uses the memory ordering chosen at construction; `load`
+/// uses its load half (`Acquire` for `AcqRel`, `Relaxed` for `Release`).
+#[hax::exclude]
+#[derive(Debug)]
+pub struct AtomicCounter {
+    value: AtomicU32,
+    ordering: Ordering,
+}
+
+#[hax::exclude]
+impl AtomicCounter {
+    /// Creates a counter at `c` using `Ordering::SeqCst`.
+    pub const fn new(c: Counter) -> AtomicCounter {
+        AtomicCounter::with_ordering(c, Ordering::SeqCst)
+    }
+
+    /// Creates a counter at `c` using `ordering`.
+    ///
+    /// `Ordering::Relaxed` suffices for a counter read only for its value,
+    /// such as a statistic. Use `AcqRel` or `SeqCst` when the counter also
+    /// publishes other memory, for example as a sequence number.
+    pub const fn with_ordering(c: Counter, ordering: Ordering) -> AtomicCounter {
+        AtomicCounter {
+            value: AtomicU32::new(c.0),
+            ordering,
+        }
+    }
+
+    /// The memory ordering of every operation.
+    pub fn ordering(&self) -> Ordering {
+        self.ordering
+    }
+
+    /// The current value.
+    pub fn load(&self) -> Counter {
+        Counter(self.value.load(load_ordering(self.ordering)))
+    }
+
+    /// Consumes the counter, returning its value.
+    pub fn into_inner(self) -> Counter {
+        Counter(self.value.into_inner())
+    }
+
+    /// Adds `n` and returns the previous value.
+    ///
+    /// The stored value is `add(old, n)`.
+    pub fn fetch_add(&self, n: Counter) -> Counter {
+        self.fetch_apply(|old| crate::add(old, n))
+    }
+
+    /// Subtracts `n` and returns the previous value.
+    ///
+    /// The stored value is `subtract(old, n)`.
+    pub fn fetch_subtract(&self, n: Counter) -> Counter {
+        self.fetch_apply(|old| crate::subtract(old, n))
+    }
+
+    /// Increments by one and returns the previous value.
+    ///
+    /// The stored value is `increment(old)`.
+    pub fn fetch_increment(&self) -> Counter {
+        self.fetch_apply(crate::increment)
+    }
+
+    /// Decrements by one and returns the previous value.
+    ///
+    /// The stored value is `decrement(old)`.
+    pub fn fetch_decrement(&self) -> Counter {
+        self.fetch_apply(crate::decrement)
+    }
+
+    /// Resets to `new_counter()` and returns the previous value.
+    ///
+    /// The stored value is `reset(old)`.
+    pub fn fetch_reset(&self) -> Counter {
+        self.fetch_apply(crate::reset)
+    }
+
+    /// Adds `n` and returns the new value, `add(old, n)`.
+    pub fn add(&self, n: Counter) -> Counter {
+        crate::add(self.fetch_add(n), n)
+    }
+
+    /// Subtracts `n` and returns the new value, `subtract(old, n)`.
+    pub fn subtract(&self, n: Counter) -> Counter {
+        crate::subtract(self.fetch_subtract(n), n)
+    }
+
+    /// Increments by one and returns the new value, `increment(old)`.
+    pub fn increment(&self) -> Counter {
+        crate::increment(self.fetch_increment())
+    }
+
+    /// Decrements by one and returns the new value, `decrement(old)`.
+    pub fn decrement(&self) -> Counter {
+        crate::decrement(self.fetch_decrement())
+    }
+
+    /// Resets to `new_counter()` and returns the new value, `reset(old)`.
+    pub fn reset(&self) -> Counter {
+        crate::reset(self.fetch_reset())
+    }
+
+    /// Atomically replaces the value `old` with `f(old)` and returns `old`.
+    fn fetch_apply(&self, f: impl Fn(Counter) -> Counter) -> Counter {
+        let result = self
+            .value
+            .fetch_update(self.ordering, load_ordering(self.ordering), |old| {
+                Some(f(Counter(old)).0)
+            });
+        // `f` always yields a value, so the update cannot be refused.
+        match result {
+            Ok(old) | Err(old) => Counter(old),
+        }
+    }
+}
+
+#[hax::exclude]
+impl Default for AtomicCounter {
+    fn default() -> AtomicCounter {
+        AtomicCounter::new(crate::new_counter())
+    }
+}
+
+#[hax::exclude]
+impl From<Counter> for AtomicCounter {
+    fn from(c: Counter) -> AtomicCounter {
+        AtomicCounter::new(c)
+    }
+}
+
+/// The strongest ordering a load may use under `ordering`.
+#[hax::exclude]
+fn load_ordering(ordering: Ordering) -> Ordering {
+    match ordering {
+        Ordering::Release => Ordering::Relaxed,
+        Ordering::AcqRel => Ordering::Acquire,
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::thread;
+
+    const ORDERINGS: [Ordering; 5] = [
+        Ordering::Relaxed,
+        Ordering::Acquire,
+        Ordering::Release,
+        Ordering::AcqRel,
+        Ordering::SeqCst,
+    ];
+
+    const SAMPLES: [u32; 5] = [0, 1, 7, u32::MAX - 1, u32::MAX];
+
+    #[test]
+    fn test_matches_pure_functions() {
+        for ordering in ORDERINGS {
+            for v in SAMPLES.map(Counter) {
+                for n in SAMPLES.map(Counter) {
+                    let a = AtomicCounter::with_ordering(v, ordering);
+                    assert_eq!(a.fetch_add(n), v);
+                    assert_eq!(a.load(), crate::add(v, n));
+                    let a = AtomicCounter::with_ordering(v, ordering);
+                    assert_eq!(a.add(n), crate::add(v, n));
+                    assert_eq!(a.subtract(n), v);
+                    assert_eq!(a.fetch_subtract(n), v);
+                    assert_eq!(a.load(), crate::subtract(v, n));
+                }
+                let a = AtomicCounter::with_ordering(v, ordering);
+                assert_eq!(a.increment(), crate::increment(v));
+                assert_eq!(a.decrement(), v);
+                assert_eq!(a.fetch_decrement(), v);
+                assert_eq!(a.fetch_increment(), crate::decrement(v));
+                assert_eq!(a.reset(), crate::reset(v));
+                assert_eq!(a.fetch_reset(), crate::new_counter());
+                assert_eq!(a.ordering(), ordering);
+            }
+        }
+    }
+
+    #[test]
+    fn test_concurrent_updates() {
+        const THREADS: u32 = 8;
+        const ROUNDS: u32 = 10_000;
+        for ordering in [Ordering::Relaxed, Ordering::AcqRel, Ordering::SeqCst] {
+            let tickets = AtomicCounter::with_ordering(Counter(0), ordering);
+            let total = AtomicCounter::with_ordering(Counter(0), ordering);
+            let claimed: Vec<Vec<Counter>> = thread::scope(|s| {
+                let workers: Vec<_> = (0..THREADS)
+                    .map(|_| {
+                        s.spawn(|| {
+                            let mut seen = Vec::new();
+                            for _ in 0..ROUNDS {
+                                seen.push(tickets.fetch_increment());
+                                total.add(Counter(3));
+                                total.fetch_subtract(Counter(1));
+                            }
+                            seen
+                        })
+                    })
+                    .collect();
+                workers.into_iter().map(|w| w.join().unwrap()).collect()
+            });
+            assert_eq!(tickets.into_inner(), Counter(THREADS * ROUNDS));
+            assert_eq!(total.into_inner(), Counter(2 * THREADS * ROUNDS));
+            // Every increment observed a different value, so none was lost.
+            let distinct: HashSet<Counter> = claimed.into_iter().flatten().collect();
+            assert_eq!(distinct.len(), (THREADS * ROUNDS) as usize);
+        }
+    }
+
+    #[test]
+    fn test_concurrent_wrapping() {
+        let counter = AtomicCounter::new(Counter(u32::MAX - 100));
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        counter.increment();
+                    }
+                });
+            }
+        });
+        assert_eq!(
+            counter.load(),
+            crate::add(Counter(u32::MAX - 100), Counter(400))
+        );
+    }
+}
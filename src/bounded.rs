@@ -0,0 +1,426 @@
+//! A counter constrained to an inclusive `[min, max]` range.
+//!
+//! Every `BoundedCounter` satisfies `min <= value <= max`. The constructors
+//! refuse values outside the range, and each operation takes a
+//! [`BoundPolicy`] deciding what happens when the result would leave it.
+
+use crate::contracts::contract;
+use crate::{Counter, CounterError};
+use hax_lib as hax;
+
+/// What a bounded operation does when its result would leave `[min, max]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundPolicy {
+    /// Stop at the nearest bound.
+    Clamp,
+    /// Wrap around within the range, so `max + 1 == min`.
+    Wrap,
+    /// Leave the counter unchanged and report `Overflow`/`Underflow`.
+    Error,
+}
+
+/// A counter value together with the inclusive range it must stay in.
+///
+/// The fields are private so the invariant `min <= value <= max` can only be
+/// established through [`BoundedCounter::new`] or [`BoundedCounter::with_bounds`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundedCounter {
+    value: Counter,
+    min: Counter,
+    max: Counter,
+}
+
+impl BoundedCounter {
+    contract! {
+        /// Creates a bounded counter holding `value`.
+        ///
+        /// # Returns
+        /// `None` unless `min <= value <= max`.
+        ensures(|result| match result {
+            Some(b) => b.invariant() && b.value == value && b.min == min && b.max == max,
+            None => !(min <= value && value <= max),
+        })
+        pub fn new(value: Counter, min: Counter, max: Counter) -> Option<BoundedCounter> {
+            if min <= value && value <= max {
+                Some(BoundedCounter { value, min, max })
+            } else {
+                None
+            }
+        }
+    }
+
+    contract! {
+        /// Creates a bounded counter starting at `min`.
+        ///
+        /// # Returns
+        /// `None` unless `min <= max`.
+        ensures(|result| match result {
+            Some(b) => b.invariant() && b.value == min,
+            None => min > max,
+        })
+        pub fn with_bounds(min: Counter, max: Counter) -> Option<BoundedCounter> {
+            BoundedCounter::new(min, min, max)
+        }
+    }
+
+    /// The current value.
+    pub fn value(self) -> Counter {
+        self.value
+    }
+
+    /// The inclusive lower bound.
+    pub fn min(self) -> Counter {
+        self.min
+    }
+
+    /// The inclusive upper bound.
+    pub fn max(self) -> Counter {
+        self.max
+    }
+
+    /// The invariant every `BoundedCounter` satisfies: `min <= value <= max`.
+    pub fn invariant(self) -> bool {
+        self.min <= self.value && self.value <= self.max
+    }
+
+    contract! {
+        /// Increments the counter by one under `policy`.
+        ///
+        /// # Properties
+        /// - `b.increment(p) == b.add(1, p)`
+        #[hax::lean::after(
+            "-- increment preserves the bounds and the invariant
+theorem Hax_basic.Bounded.Impl.increment_preserves_invariant
+  (b : Hax_basic.Bounded.BoundedCounter) (p : Hax_basic.Bounded.BoundPolicy) :
+  ⦃ ⌜ b.min._0 ≤ b.value._0 ∧ b.value._0 ≤ b.max._0 ⌝ ⦄
+  (Hax_basic.Bounded.Impl.increment b p)
+  ⦃ ⇓ result => ⌜ ∀ r, result = Core.Result.Result.Ok r →
+      r.min = b.min ∧ r.max = b.max ∧ r.min._0 ≤ r.value._0 ∧ r.value._0 ≤ r.max._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Bounded.Impl.increment, Hax_basic.Bounded.Impl.add,
+    Hax_basic.Bounded.Impl.span]
+  all_goals (simp_all [Nat.mod_lt]; try omega)
+"
+        )]
+        requires(self.invariant())
+        ensures(|result| match result {
+            Ok(b) => b.invariant() && b.min == self.min && b.max == self.max,
+            Err(_) => true,
+        })
+        pub fn increment(self, policy: BoundPolicy) -> Result<BoundedCounter, CounterError> {
+            self.add(Counter(1), policy)
+        }
+    }
+
+    contract! {
+        /// Decrements the counter by one under `policy`.
+        ///
+        /// # Properties
+        /// - `b.decrement(p) == b.subtract(1, p)`
+        #[hax::lean::after(
+            "-- decrement preserves the bounds and the invariant
+theorem Hax_basic.Bounded.Impl.decrement_preserves_invariant
+  (b : Hax_basic.Bounded.BoundedCounter) (p : Hax_basic.Bounded.BoundPolicy) :
+  ⦃ ⌜ b.min._0 ≤ b.value._0 ∧ b.value._0 ≤ b.max._0 ⌝ ⦄
+  (Hax_basic.Bounded.Impl.decrement b p)
+  ⦃ ⇓ result => ⌜ ∀ r, result = Core.Result.Result.Ok r →
+      r.min = b.min ∧ r.max = b.max ∧ r.min._0 ≤ r.value._0 ∧ r.value._0 ≤ r.max._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Bounded.Impl.decrement, Hax_basic.Bounded.Impl.subtract,
+    Hax_basic.Bounded.Impl.span]
+  all_goals (simp_all [Nat.mod_lt]; try omega)
+"
+        )]
+        requires(self.invariant())
+        ensures(|result| match result {
+            Ok(b) => b.invariant() && b.min == self.min && b.max == self.max,
+            Err(_) => true,
+        })
+        pub fn decrement(self, policy: BoundPolicy) -> Result<BoundedCounter, CounterError> {
+            self.subtract(Counter(1), policy)
+        }
+    }
+
+    contract! {
+        /// Adds `n` to the counter under `policy`.
+        ///
+        /// # Returns
+        /// - `Clamp`: `min(value + n, max)`
+        /// - `Wrap`: `min + (value - min + n) mod (max - min + 1)`
+        /// - `Error`: `value + n`, or `Err(CounterError::Overflow { c: value, n })`
+        ///   when that exceeds `max`
+        ///
+        /// # Properties
+        /// - `b.add(0, p) == Ok(b)`
+        /// - the result has the same bounds as `b` and satisfies the invariant
+        #[hax::lean::after(
+            "-- add preserves the bounds and the invariant under every policy
+theorem Hax_basic.Bounded.Impl.add_preserves_invariant
+  (b : Hax_basic.Bounded.BoundedCounter) (n : Hax_basic.Counter) (p : Hax_basic.Bounded.BoundPolicy) :
+  ⦃ ⌜ b.min._0 ≤ b.value._0 ∧ b.value._0 ≤ b.max._0 ⌝ ⦄
+  (Hax_basic.Bounded.Impl.add b n p)
+  ⦃ ⇓ result => ⌜ ∀ r, result = Core.Result.Result.Ok r →
+      r.min = b.min ∧ r.max = b.max ∧ r.min._0 ≤ r.value._0 ∧ r.value._0 ≤ r.max._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Bounded.Impl.add, Hax_basic.Bounded.Impl.span]
+  all_goals (simp_all [Nat.mod_lt]; try omega)
+"
+        )]
+        requires(self.invariant())
+        ensures(|result| match result {
+            Ok(b) => b.invariant() && b.min == self.min && b.max == self.max,
+            Err(_) => true,
+        })
+        pub fn add(self, n: Counter, policy: BoundPolicy) -> Result<BoundedCounter, CounterError> {
+            let headroom = self.max.0 - self.value.0;
+            match policy {
+                BoundPolicy::Clamp => {
+                    let value = if n.0 > headroom {
+                        self.max
+                    } else {
+                        Counter(self.value.0 + n.0)
+                    };
+                    Ok(BoundedCounter { value, ..self })
+                }
+                BoundPolicy::Wrap => {
+                    let offset = (self.value.0 - self.min.0) as u64;
+                    let value = Counter(self.min.0 + ((offset + n.0 as u64) % self.span()) as u32);
+                    Ok(BoundedCounter { value, ..self })
+                }
+                BoundPolicy::Error => {
+                    if n.0 > headroom {
+                        Err(CounterError::Overflow { c: self.value, n })
+                    } else {
+                        Ok(BoundedCounter {
+                            value: Counter(self.value.0 + n.0),
+                            ..self
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    contract! {
+        /// Subtracts `n` from the counter under `policy`.
+        ///
+        /// # Returns
+        /// - `Clamp`: `max(value - n, min)`
+        /// - `Wrap`: `min + (value - min - n) mod (max - min + 1)`
+        /// - `Error`: `value - n`, or `Err(CounterError::Underflow { c: value, n })`
+        ///   when that is below `min`
+        ///
+        /// # Properties
+        /// - `b.subtract(0, p) == Ok(b)`
+        /// - the result has the same bounds as `b` and satisfies the invariant
+        #[hax::lean::after(
+            "-- subtract preserves the bounds and the invariant under every policy
+theorem Hax_basic.Bounded.Impl.subtract_preserves_invariant
+  (b : Hax_basic.Bounded.BoundedCounter) (n : Hax_basic.Counter) (p : Hax_basic.Bounded.BoundPolicy) :
+  ⦃ ⌜ b.min._0 ≤ b.value._0 ∧ b.value._0 ≤ b.max._0 ⌝ ⦄
+  (Hax_basic.Bounded.Impl.subtract b n p)
+  ⦃ ⇓ result => ⌜ ∀ r, result = Core.Result.Result.Ok r →
+      r.min = b.min ∧ r.max = b.max ∧ r.min._0 ≤ r.value._0 ∧ r.value._0 ≤ r.max._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Bounded.Impl.subtract, Hax_basic.Bounded.Impl.span]
+  all_goals (simp_all [Nat.mod_lt]; try omega)
+"
+        )]
+        requires(self.invariant())
+        ensures(|result| match result {
+            Ok(b) => b.invariant() && b.min == self.min && b.max == self.max,
+            Err(_) => true,
+        })
+        pub fn subtract(
+            self,
+            n: Counter,
+            policy: BoundPolicy,
+        ) -> Result<BoundedCounter, CounterError> {
+            let headroom = self.value.0 - self.min.0;
+            match policy {
+                BoundPolicy::Clamp => {
+                    let value = if n.0 > headroom {
+                        self.min
+                    } else {
+                        Counter(self.value.0 - n.0)
+                    };
+                    Ok(BoundedCounter { value, ..self })
+                }
+                BoundPolicy::Wrap => {
+                    let span = self.span();
+                    let value = Counter(
+                        self.min.0 + ((headroom as u64 + span - n.0 as u64 % span) % span) as u32,
+                    );
+                    Ok(BoundedCounter { value, ..self })
+                }
+                BoundPolicy::Error => {
+                    if n.0 > headroom {
+                        Err(CounterError::Underflow { c: self.value, n })
+                    } else {
+                        Ok(BoundedCounter {
+                            value: Counter(self.value.0 - n.0),
+                            ..self
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    contract! {
+        /// Resets the counter to its lower bound.
+        requires(self.invariant())
+        ensures(|result| result.invariant() && result.value == self.min)
+        pub fn reset(self) -> BoundedCounter {
+            BoundedCounter {
+                value: self.min,
+                ..self
+            }
+        }
+    }
+
+    /// Number of values in `[min, max]`, which is at most `2^32`.
+    fn span(self) -> u64 {
+        (self.max.0 - self.min.0) as u64 + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counter(value: u32, min: u32, max: u32) -> BoundedCounter {
+        BoundedCounter::new(Counter(value), Counter(min), Counter(max)).unwrap()
+    }
+
+    #[test]
+    fn test_new_enforces_invariant() {
+        assert!(BoundedCounter::new(Counter(5), Counter(1), Counter(10)).is_some());
+        assert!(BoundedCounter::new(Counter(1), Counter(1), Counter(1)).is_some());
+        assert!(BoundedCounter::new(Counter(0), Counter(1), Counter(10)).is_none());
+        assert!(BoundedCounter::new(Counter(11), Counter(1), Counter(10)).is_none());
+        assert!(BoundedCounter::with_bounds(Counter(10), Counter(1)).is_none());
+        assert_eq!(
+            BoundedCounter::with_bounds(Counter(3), Counter(7))
+                .unwrap()
+                .value(),
+            Counter(3)
+        );
+    }
+
+    #[test]
+    fn test_clamp() {
+        let b = counter(8, 2, 10);
+        assert_eq!(
+            b.add(Counter(1), BoundPolicy::Clamp).unwrap().value(),
+            Counter(9)
+        );
+        assert_eq!(
+            b.add(Counter(5), BoundPolicy::Clamp).unwrap().value(),
+            Counter(10)
+        );
+        assert_eq!(
+            b.subtract(Counter(100), BoundPolicy::Clamp)
+                .unwrap()
+                .value(),
+            Counter(2)
+        );
+        assert_eq!(
+            b.add(Counter::MAX, BoundPolicy::Clamp).unwrap().value(),
+            Counter(10)
+        );
+    }
+
+    #[test]
+    fn test_wrap() {
+        let b = counter(9, 2, 10);
+        assert_eq!(b.increment(BoundPolicy::Wrap).unwrap().value(), Counter(10));
+        assert_eq!(
+            b.add(Counter(2), BoundPolicy::Wrap).unwrap().value(),
+            Counter(2)
+        );
+        assert_eq!(
+            b.add(Counter(9 * 3), BoundPolicy::Wrap).unwrap().value(),
+            Counter(9)
+        );
+        let low = counter(2, 2, 10);
+        assert_eq!(
+            low.decrement(BoundPolicy::Wrap).unwrap().value(),
+            Counter(10)
+        );
+        assert_eq!(
+            low.subtract(Counter(10), BoundPolicy::Wrap)
+                .unwrap()
+                .value(),
+            Counter(10)
+        );
+    }
+
+    #[test]
+    fn test_wrap_full_range_matches_core() {
+        let b = counter(u32::MAX - 1, 0, u32::MAX);
+        assert_eq!(
+            b.add(Counter(5), BoundPolicy::Wrap).unwrap().value(),
+            crate::add(Counter(u32::MAX - 1), Counter(5))
+        );
+        assert_eq!(
+            counter(3, 0, u32::MAX)
+                .subtract(Counter(5), BoundPolicy::Wrap)
+                .unwrap()
+                .value(),
+            crate::subtract(Counter(3), Counter(5))
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        let b = counter(8, 2, 10);
+        assert_eq!(
+            b.add(Counter(2), BoundPolicy::Error).unwrap().value(),
+            Counter(10)
+        );
+        assert_eq!(
+            b.add(Counter(3), BoundPolicy::Error),
+            Err(CounterError::Overflow {
+                c: Counter(8),
+                n: Counter(3)
+            })
+        );
+        assert_eq!(
+            b.subtract(Counter(7), BoundPolicy::Error),
+            Err(CounterError::Underflow {
+                c: Counter(8),
+                n: Counter(7)
+            })
+        );
+    }
+
+    #[test]
+    fn test_bounds_preserved() {
+        let b = counter(5, 3, 7);
+        for policy in [BoundPolicy::Clamp, BoundPolicy::Wrap, BoundPolicy::Error] {
+            for n in [0, 1, 2, 4, 5, 100, u32::MAX].map(Counter) {
+                for r in [b.add(n, policy), b.subtract(n, policy)]
+                    .into_iter()
+                    .flatten()
+                {
+                    assert!(r.invariant());
+                    assert_eq!((r.min(), r.max()), (Counter(3), Counter(7)));
+                }
+            }
+        }
+        assert_eq!(b.reset().value(), Counter(3));
+    }
+
+    #[cfg(feature = "runtime-contracts")]
+    #[test]
+    #[should_panic(expected = "hax_basic::bounded::add: precondition violated: self.invariant()")]
+    fn test_runtime_contracts_reject_broken_invariant() {
+        let broken = BoundedCounter {
+            value: Counter(11),
+            min: Counter(1),
+            max: Counter(10),
+        };
+        let _ = broken.add(Counter(1), BoundPolicy::Clamp);
+    }
+}
@@ -0,0 +1,337 @@
+//! Logical clocks: counters with a merge rule.
+//!
+//! A [`LamportClock`] is a single [`Counter`] that ticks on every local event
+//! and jumps past any timestamp it receives, so if event `a` happened before
+//! event `b` then `L(a) < L(b)`. A [`VectorClock<R>`] keeps one `Counter` per
+//! replica and captures the converse too: `a` happened before `b` exactly
+//! when `V(a) < V(b)` pointwise.
+//!
+//! Both tick with [`crate::increment`], so a clock at `Counter::MAX` wraps to
+//! zero like any other counter. The clock condition is proven for the
+//! non-wrapping range, below `Counter::MAX`.
+
+use crate::contracts::contract;
+use crate::Counter;
+use hax_lib as hax;
+
+/// A Lamport clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LamportClock {
+    time: Counter,
+}
+
+impl LamportClock {
+    /// Creates a clock at `new_counter()`.
+    pub fn new() -> LamportClock {
+        LamportClock::starting_at(crate::new_counter())
+    }
+
+    /// Creates a clock at `time`, for example one restored from storage.
+    pub fn starting_at(time: Counter) -> LamportClock {
+        LamportClock { time }
+    }
+
+    /// The current timestamp.
+    pub fn time(self) -> Counter {
+        self.time
+    }
+
+    contract! {
+        /// Records a local event, including sending a message.
+        ///
+        /// # Returns
+        /// A clock at `increment(time)`. Send its `time()` with the message.
+        ///
+        /// # Properties
+        /// - `c.tick().time() > c.time()` when `c.time() < Counter::MAX`
+        #[hax::lean::after(
+            "-- Clock condition for local events: a tick is strictly later, below MAX
+theorem Hax_basic.Clock.Impl.tick_later (c : Hax_basic.Clock.LamportClock)
+  (h : c.time._0 < 4294967295) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Clock.Impl.tick c)
+  ⦃ ⇓ r => ⌜ c.time._0 < r.time._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Clock.Impl.tick, Hax_basic.increment_eq, Core.Num.Impl_8.wrapping_add]
+  all_goals (simp_all; try omega)
+"
+        )]
+        ensures(|result| result.time == crate::increment(self.time)
+            && (self.time == Counter::MAX || result.time.0 > self.time.0))
+        pub fn tick(self) -> LamportClock {
+            LamportClock {
+                time: crate::increment(self.time),
+            }
+        }
+    }
+
+    contract! {
+        /// Records receiving a message stamped `remote`.
+        ///
+        /// # Returns
+        /// A clock at `increment(max(time, remote))`.
+        ///
+        /// # Properties
+        /// - `c.receive(t).time() > t` and `c.receive(t).time() > c.time()`
+        ///   when `max(c.time(), t) < Counter::MAX`
+        #[hax::lean::after(
+            "-- Clock condition for messages: the receive event is later than both the
+-- send event and every earlier local event, below MAX
+theorem Hax_basic.Clock.Impl.receive_later (c : Hax_basic.Clock.LamportClock)
+  (remote : Hax_basic.Counter)
+  (hc : c.time._0 < 4294967295) (hr : remote._0 < 4294967295) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Clock.Impl.receive c remote)
+  ⦃ ⇓ r => ⌜ c.time._0 < r.time._0 ∧ remote._0 < r.time._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Clock.Impl.receive, Hax_basic.max, Hax_basic.increment_eq,
+    Core.Num.Impl_8.wrapping_add]
+  all_goals (split <;> simp_all <;> omega)
+"
+        )]
+        ensures(|result| result.time == crate::increment(crate::max(self.time, remote)))
+        pub fn receive(self, remote: Counter) -> LamportClock {
+            LamportClock {
+                time: crate::increment(crate::max(self.time, remote)),
+            }
+        }
+    }
+}
+
+/// A vector clock over `R` replicas, identified by the indices `0..R`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VectorClock<const R: usize> {
+    entries: [Counter; R],
+}
+
+impl<const R: usize> VectorClock<R> {
+    /// Creates a clock with every entry at `new_counter()`.
+    pub fn new() -> VectorClock<R> {
+        VectorClock {
+            entries: [crate::new_counter(); R],
+        }
+    }
+
+    /// Creates a clock from its entries, indexed by replica id.
+    pub fn from_entries(entries: [Counter; R]) -> VectorClock<R> {
+        VectorClock { entries }
+    }
+
+    /// The entries, indexed by replica id.
+    pub fn entries(self) -> [Counter; R] {
+        self.entries
+    }
+
+    contract! {
+        /// Records a local event at `replica`.
+        ///
+        /// # Returns
+        /// The clock with `replica`'s entry incremented.
+        ///
+        /// # Panics
+        /// If `replica >= R`.
+        ///
+        /// # Properties
+        /// - `v.happened_before(v.tick(i))` when `v.entries()[i] < Counter::MAX`
+        #[hax::lean::after(
+            "-- Clock condition for local events: a tick is strictly later, below MAX
+theorem Hax_basic.Clock.Impl_1.tick_later (R : usize) (v : Hax_basic.Clock.VectorClock R)
+  (replica : usize) (hi : replica.toNat < R.toNat)
+  (h : v.entries.toList[replica.toNat]!._0 < 4294967295) :
+  ⦃ ⌜ True ⌝ ⦄
+  (do let r ← Hax_basic.Clock.Impl_1.tick R v replica; Hax_basic.Clock.Impl_1.happened_before R v r)
+  ⦃ ⇓ b => ⌜ b = true ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Clock.Impl_1.tick, Hax_basic.Clock.Impl_1.happened_before,
+    Hax_basic.Clock.Impl_1.le, Hax_basic.increment_eq, Core.Num.Impl_8.wrapping_add]
+  case inv =>
+    exact ⇓ ⟨j, acc⟩ => ⌜ acc = true ⌝
+  all_goals (simp_all [Vector.getElem_set]; try grind)
+"
+        )]
+        requires(replica < R)
+        pub fn tick(self, replica: usize) -> VectorClock<R> {
+            let mut entries = self.entries;
+            entries[replica] = crate::increment(self.entries[replica]);
+            VectorClock { entries }
+        }
+    }
+
+    /// Combines the knowledge of two clocks.
+    ///
+    /// # Returns
+    /// The pointwise maximum of the entries.
+    ///
+    /// # Properties
+    /// - `a.le(a.merge(b))` and `b.le(a.merge(b))`
+    /// - `a.merge(b) == b.merge(a)`
+    #[hax::lean::after(
+        "-- merge is an upper bound of both clocks
+theorem Hax_basic.Clock.Impl_1.merge_spec (R : usize) (a b : Hax_basic.Clock.VectorClock R) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Clock.Impl_1.merge R a b)
+  ⦃ ⇓ r => ⌜ ∀ i < R.toNat, r.entries.toList[i]!._0
+      = max a.entries.toList[i]!._0 b.entries.toList[i]!._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Clock.Impl_1.merge, Hax_basic.max]
+  case inv =>
+    exact ⇓ ⟨j, entries⟩ => ⌜ ∀ i < R.toNat, entries.toList[i]!._0
+      = if i < j then max a.entries.toList[i]!._0 b.entries.toList[i]!._0
+        else a.entries.toList[i]!._0 ⌝
+  all_goals (simp_all [Nat.lt_succ_iff_lt_or_eq]; try grind)
+"
+    )]
+    // `merge_spec` proves this loop with an invariant over the merged prefix.
+    #[allow(clippy::needless_range_loop)]
+    pub fn merge(self, other: VectorClock<R>) -> VectorClock<R> {
+        let mut entries = self.entries;
+        for i in 0..R {
+            entries[i] = crate::max(self.entries[i], other.entries[i]);
+        }
+        VectorClock { entries }
+    }
+
+    contract! {
+        /// Records receiving a message stamped `remote` at `replica`.
+        ///
+        /// # Returns
+        /// `self.merge(remote).tick(replica)`.
+        ///
+        /// # Panics
+        /// If `replica >= R`.
+        ///
+        /// # Properties
+        /// - `remote.happened_before(v.receive(i, remote))` and
+        ///   `v.happened_before(v.receive(i, remote))`, below `Counter::MAX`
+        #[hax::lean::after(
+            "-- Clock condition for messages: the receive event is later than both the
+-- send event and every earlier local event, below MAX
+theorem Hax_basic.Clock.Impl_1.receive_later (R : usize) (v remote : Hax_basic.Clock.VectorClock R)
+  (replica : usize) (hi : replica.toNat < R.toNat)
+  (h : max v.entries.toList[replica.toNat]!._0 remote.entries.toList[replica.toNat]!._0
+    < 4294967295) :
+  ⦃ ⌜ True ⌝ ⦄
+  (do let r ← Hax_basic.Clock.Impl_1.receive R v replica remote
+      let a ← Hax_basic.Clock.Impl_1.happened_before R v r
+      let b ← Hax_basic.Clock.Impl_1.happened_before R remote r
+      pure (a && b))
+  ⦃ ⇓ b => ⌜ b = true ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Clock.Impl_1.receive, Hax_basic.Clock.Impl_1.merge_spec,
+    Hax_basic.Clock.Impl_1.tick, Hax_basic.Clock.Impl_1.happened_before,
+    Hax_basic.Clock.Impl_1.le, Hax_basic.increment_eq, Core.Num.Impl_8.wrapping_add]
+  case inv =>
+    exact ⇓ ⟨j, acc⟩ => ⌜ acc = true ⌝
+  all_goals (simp_all [Vector.getElem_set]; try grind)
+"
+        )]
+        requires(replica < R)
+        pub fn receive(self, replica: usize, remote: VectorClock<R>) -> VectorClock<R> {
+            self.merge(remote).tick(replica)
+        }
+    }
+
+    /// Whether every entry of `self` is at most the matching entry of `other`.
+    // `tick_later` and `receive_later` prove this loop with an invariant on `result`.
+    #[allow(clippy::needless_range_loop)]
+    pub fn le(self, other: VectorClock<R>) -> bool {
+        let mut result = true;
+        for i in 0..R {
+            result = result && self.entries[i].0 <= other.entries[i].0;
+        }
+        result
+    }
+
+    /// Whether the event stamped `self` happened before the one stamped `other`.
+    ///
+    /// # Returns
+    /// `true` when `self.le(other)` and the clocks differ.
+    ///
+    /// # Properties
+    /// - irreflexive: `!a.happened_before(a)`
+    /// - antisymmetric: not both `a.happened_before(b)` and `b.happened_before(a)`
+    pub fn happened_before(self, other: VectorClock<R>) -> bool {
+        self.le(other) && self != other
+    }
+
+    /// Whether neither event happened before the other.
+    ///
+    /// # Returns
+    /// `true` when neither clock is `le` the other.
+    pub fn concurrent(self, other: VectorClock<R>) -> bool {
+        !self.le(other) && !other.le(self)
+    }
+}
+
+impl<const R: usize> Default for VectorClock<R> {
+    fn default() -> VectorClock<R> {
+        VectorClock::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lamport_clock_condition() {
+        let a = LamportClock::new().tick();
+        assert_eq!(a.time(), Counter(1));
+        // `b` receives a message sent at `a`, then ticks locally.
+        let b = LamportClock::starting_at(Counter(0)).receive(a.time());
+        assert!(b.time() > a.time());
+        let b = b.tick();
+        // `a` receives a reply from `b` and jumps past it.
+        let a = a.tick().tick().tick().receive(b.time());
+        assert_eq!(a.time(), Counter(5));
+        let ahead = LamportClock::starting_at(Counter(9)).receive(Counter(2));
+        assert_eq!(ahead.time(), Counter(10));
+        assert_eq!(
+            LamportClock::starting_at(Counter::MAX).tick(),
+            LamportClock::new()
+        );
+    }
+
+    #[test]
+    fn test_vector_clock_ordering() {
+        let start = VectorClock::<3>::new();
+        let a = start.tick(0);
+        let b = start.tick(1);
+        assert!(start.happened_before(a) && start.happened_before(b));
+        assert!(a.concurrent(b) && b.concurrent(a));
+        assert!(!a.happened_before(a) && !a.concurrent(a));
+
+        // `b` receives a message sent at `a`.
+        let b2 = b.receive(1, a);
+        assert_eq!(b2.entries(), [Counter(1), Counter(2), Counter(0)]);
+        assert!(a.happened_before(b2) && b.happened_before(b2));
+        assert!(!b2.happened_before(a) && !a.concurrent(b2));
+        assert_eq!(a.merge(b), b.merge(a));
+        assert!(a.le(a.merge(b)) && b.le(a.merge(b)));
+    }
+
+    #[test]
+    fn test_clock_condition_over_a_history() {
+        // Each replica sends to the next after every local event; every event
+        // happens before every later event on the same chain.
+        let mut replicas = [VectorClock::<3>::new(); 3];
+        let mut lamport = [LamportClock::new(); 3];
+        let mut history = Vec::new();
+        for step in 0..30 {
+            let i = step % 3;
+            let k = (i + 1) % 3;
+            replicas[i] = replicas[i].tick(i);
+            lamport[i] = lamport[i].tick();
+            replicas[k] = replicas[k].receive(k, replicas[i]);
+            lamport[k] = lamport[k].receive(lamport[i].time());
+            history.push((replicas[i], lamport[i].time()));
+            history.push((replicas[k], lamport[k].time()));
+        }
+        for (x, (va, la)) in history.iter().enumerate() {
+            for (vb, lb) in &history[x + 1..] {
+                assert!(va.happened_before(*vb));
+                assert!(la < lb);
+            }
+        }
+    }
+}
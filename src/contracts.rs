@@ -0,0 +1,85 @@
+//! One source for each function contract.
+//!
+//! A function's pre- and postcondition are written once, as the `requires`
+//! and `ensures` clauses of a [`contract!`] item. The macro emits them as the
+//! `#[hax::requires]` and `#[hax::ensures]` attributes that extraction and the
+//! proofs see. With the `runtime-contracts` feature enabled it also evaluates
+//! the same conditions in ordinary builds and panics with a descriptive
+//! message when one is violated. Without the feature the function is emitted
+//! exactly as written, so the extracted code is unaffected.
+//!
+//! ```text
+//! contract! {
+//!     /// Adds `n` to the counter.
+//!     requires(self.invariant())
+//!     ensures(|result| result.invariant())
+//!     pub fn add(self, n: Counter) -> Self {
+//!         ...
+//!     }
+//! }
+//! ```
+//!
+//! Under the feature the body runs in a closure, so an early `return` or `?`
+//! still reaches the postcondition check.
+
+/// Emits a function with its hax contract and, with `runtime-contracts`,
+/// runtime checks of the same conditions.
+#[cfg(feature = "runtime-contracts")]
+macro_rules! contract {
+    (
+        $(#[$attr:meta])*
+        $(requires($pre:expr))?
+        $(ensures(|$result:ident| $post:expr))?
+        $(pub $(($($vis:tt)*))?)? fn $name:ident $(<$($gen:ident: $bound:path),*>)?
+            ($($params:tt)*) -> $ret:ty $body:block
+    ) => {
+        $(#[$attr])*
+        $(#[::hax_lib::requires($pre)])?
+        $(#[::hax_lib::ensures(|$result| $post)])?
+        $(pub $(($($vis)*))?)? fn $name $(<$($gen: $bound),*>)? ($($params)*) -> $ret {
+            $(
+                if !$pre {
+                    panic!(
+                        "{}: precondition violated: {}",
+                        concat!(module_path!(), "::", stringify!($name)),
+                        stringify!($pre)
+                    );
+                }
+            )?
+            #[allow(clippy::redundant_closure_call)]
+            let result: $ret = (|| -> $ret { $body })();
+            $(
+                let $result = result;
+                if !$post {
+                    panic!(
+                        "{}: postcondition violated: {}",
+                        concat!(module_path!(), "::", stringify!($name)),
+                        stringify!($post)
+                    );
+                }
+                let result = $result;
+            )?
+            result
+        }
+    };
+}
+
+/// Emits a function with its hax contract and, with `runtime-contracts`,
+/// runtime checks of the same conditions.
+#[cfg(not(feature = "runtime-contracts"))]
+macro_rules! contract {
+    (
+        $(#[$attr:meta])*
+        $(requires($pre:expr))?
+        $(ensures(|$result:ident| $post:expr))?
+        $(pub $(($($vis:tt)*))?)? fn $name:ident $(<$($gen:ident: $bound:path),*>)?
+            ($($params:tt)*) -> $ret:ty $body:block
+    ) => {
+        $(#[$attr])*
+        $(#[::hax_lib::requires($pre)])?
+        $(#[::hax_lib::ensures(|$result| $post)])?
+        $(pub $(($($vis)*))?)? fn $name $(<$($gen: $bound),*>)? ($($params)*) -> $ret $body
+    };
+}
+
+pub(crate) use contract;
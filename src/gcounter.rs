@@ -0,0 +1,353 @@
+//! A grow-only counter CRDT.
+//!
+//! A [`GCounter<R>`] is replicated across `R` replicas, identified by the
+//! indices `0..R`. Each replica only advances its own slot, and replicas
+//! converge by exchanging state and taking the pointwise maximum with
+//! [`GCounter::merge`]. Because `merge` is commutative, associative and
+//! idempotent (proven in Lean below), replicas that have seen the same
+//! updates hold the same state, whatever order and however often they merged.
+//!
+//! Slots are advanced with [`crate::checked_add`]: a slot that wrapped would
+//! look smaller than before and be lost by the next merge, so a slot at
+//! `Counter::MAX` reports `CounterError::Overflow` instead.
+
+use crate::contracts::contract;
+use crate::{Counter, CounterError};
+use hax_lib as hax;
+
+/// A grow-only counter over `R` replicas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GCounter<const R: usize> {
+    slots: [Counter; R],
+}
+
+impl<const R: usize> GCounter<R> {
+    /// Creates a counter with every slot at `new_counter()`.
+    pub fn new() -> GCounter<R> {
+        GCounter {
+            slots: [crate::new_counter(); R],
+        }
+    }
+
+    /// Creates a counter from its slots, indexed by replica id.
+    pub fn from_slots(slots: [Counter; R]) -> GCounter<R> {
+        GCounter { slots }
+    }
+
+    /// The slots, indexed by replica id.
+    pub fn slots(self) -> [Counter; R] {
+        self.slots
+    }
+
+    contract! {
+        /// The number of updates made at `replica`.
+        ///
+        /// # Panics
+        /// If `replica >= R`.
+        requires(replica < R)
+        pub fn slot(self, replica: usize) -> Counter {
+            self.slots[replica]
+        }
+    }
+
+    contract! {
+        /// Records one update at `replica`.
+        ///
+        /// # Returns
+        /// The counter with `replica`'s slot incremented, or
+        /// `Err(CounterError::Overflow)` when that slot is at `Counter::MAX`.
+        ///
+        /// # Panics
+        /// If `replica >= R`.
+        requires(replica < R)
+        pub fn increment(self, replica: usize) -> Result<GCounter<R>, CounterError> {
+            self.add(replica, Counter(1))
+        }
+    }
+
+    contract! {
+        /// Records `n` updates at `replica`.
+        ///
+        /// # Returns
+        /// The counter with `checked_add(slot, n)` in `replica`'s slot and every
+        /// other slot unchanged, or `Err(CounterError::Overflow)` when the slot
+        /// would exceed `Counter::MAX`.
+        ///
+        /// # Panics
+        /// If `replica >= R`.
+        ///
+        /// # Properties
+        /// - `g.add(i, n)?.value() == g.value() + n`
+        requires(replica < R)
+        ensures(|result| match result {
+            Ok(g) => g.value() == self.value() + n.0 as u64,
+            Err(_) => self.slots[replica].0 as u64 + n.0 as u64 > u32::MAX as u64,
+        })
+        pub fn add(self, replica: usize, n: Counter) -> Result<GCounter<R>, CounterError> {
+            let mut slots = self.slots;
+            slots[replica] = crate::checked_add(self.slots[replica], n)?;
+            Ok(GCounter { slots })
+        }
+    }
+
+    /// Merges the state of another replica.
+    ///
+    /// # Returns
+    /// The pointwise maximum of the two counters' slots.
+    ///
+    /// # Properties
+    /// - `a.merge(b) == b.merge(a)`
+    /// - `a.merge(b.merge(c)) == a.merge(b).merge(c)`
+    /// - `a.merge(a) == a`
+    #[hax::lean::after(
+        "-- merge takes the pointwise maximum of the slots
+theorem Hax_basic.Gcounter.Impl.merge_spec (R : usize) (a b : Hax_basic.Gcounter.GCounter R) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Gcounter.Impl.merge R a b)
+  ⦃ ⇓ r => ⌜ ∀ i < R.toNat, r.slots.toList[i]!._0
+      = max a.slots.toList[i]!._0 b.slots.toList[i]!._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Gcounter.Impl.merge, Hax_basic.max]
+  case inv =>
+    exact ⇓ ⟨j, slots⟩ => ⌜ ∀ i < R.toNat, slots.toList[i]!._0
+      = if i < j then max a.slots.toList[i]!._0 b.slots.toList[i]!._0
+        else a.slots.toList[i]!._0 ⌝
+  all_goals (simp_all [Nat.lt_succ_iff_lt_or_eq]; try grind)
+
+-- Counters with equal slots are equal
+theorem Hax_basic.Gcounter.slots_ext (R : usize) (x y : Hax_basic.Gcounter.GCounter R)
+  (h : ∀ i < R.toNat, x.slots.toList[i]!._0 = y.slots.toList[i]!._0) : x = y := by
+  cases x; cases y; congr; apply Vector.ext; grind
+
+-- Law: merge is commutative
+theorem Hax_basic.Gcounter.Impl.merge_comm (R : usize) (a b : Hax_basic.Gcounter.GCounter R) :
+  Hax_basic.Gcounter.Impl.merge R a b = Hax_basic.Gcounter.Impl.merge R b a := by
+  have ha := Hax_basic.Gcounter.Impl.merge_spec R a b
+  have hb := Hax_basic.Gcounter.Impl.merge_spec R b a
+  mvcgen at ha hb
+  congr 1
+  exact Hax_basic.Gcounter.slots_ext R _ _ (by intro i hi; simp_all [Nat.max_comm])
+
+-- Law: merge is associative
+theorem Hax_basic.Gcounter.Impl.merge_assoc (R : usize)
+  (a b c : Hax_basic.Gcounter.GCounter R) :
+  (do let bc ← Hax_basic.Gcounter.Impl.merge R b c; Hax_basic.Gcounter.Impl.merge R a bc)
+    = (do let ab ← Hax_basic.Gcounter.Impl.merge R a b; Hax_basic.Gcounter.Impl.merge R ab c) := by
+  mvcgen [Hax_basic.Gcounter.Impl.merge_spec]
+  congr 1
+  exact Hax_basic.Gcounter.slots_ext R _ _ (by intro i hi; simp_all [Nat.max_assoc])
+
+-- Law: merge is idempotent
+theorem Hax_basic.Gcounter.Impl.merge_idem (R : usize) (a : Hax_basic.Gcounter.GCounter R) :
+  Hax_basic.Gcounter.Impl.merge R a a = pure a := by
+  have h := Hax_basic.Gcounter.Impl.merge_spec R a a
+  mvcgen at h
+  congr 1
+  exact Hax_basic.Gcounter.slots_ext R _ _ (by intro i hi; simp_all)
+"
+    )]
+    // `merge_spec` proves this loop with an invariant over the merged prefix.
+    #[allow(clippy::needless_range_loop)]
+    pub fn merge(self, other: GCounter<R>) -> GCounter<R> {
+        let mut slots = self.slots;
+        for i in 0..R {
+            slots[i] = crate::max(self.slots[i], other.slots[i]);
+        }
+        GCounter { slots }
+    }
+
+    /// The part of `self` that `since` has not seen, to ship instead of the
+    /// full state.
+    ///
+    /// # Returns
+    /// A counter holding each slot of `self` that is ahead of the matching
+    /// slot of `since`, and zero in every other slot.
+    ///
+    /// # Properties
+    /// - `r.merge(a.delta_since(s)) == r.merge(a)` whenever `s.le(r)`
+    /// - `a.delta_since(a) == GCounter::new()`
+    #[hax::lean::after(
+        "-- delta_since keeps exactly the slots that are ahead of `since`
+theorem Hax_basic.Gcounter.Impl.delta_since_spec (R : usize)
+  (a s : Hax_basic.Gcounter.GCounter R) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Gcounter.Impl.delta_since R a s)
+  ⦃ ⇓ d => ⌜ ∀ i < R.toNat, d.slots.toList[i]!._0
+      = if s.slots.toList[i]!._0 < a.slots.toList[i]!._0 then a.slots.toList[i]!._0 else 0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Gcounter.Impl.delta_since]
+  case inv =>
+    exact ⇓ ⟨j, slots⟩ => ⌜ ∀ i < R.toNat, slots.toList[i]!._0
+      = if i < j then
+          (if s.slots.toList[i]!._0 < a.slots.toList[i]!._0 then a.slots.toList[i]!._0 else 0)
+        else 0 ⌝
+  all_goals (simp_all [Nat.lt_succ_iff_lt_or_eq]; try grind)
+
+-- Law: merging the delta into any replica that has seen `since` equals
+-- merging the full state
+theorem Hax_basic.Gcounter.Impl.merge_delta_since (R : usize)
+  (r a s : Hax_basic.Gcounter.GCounter R)
+  (h : ∀ i < R.toNat, s.slots.toList[i]!._0 ≤ r.slots.toList[i]!._0) :
+  (do let d ← Hax_basic.Gcounter.Impl.delta_since R a s; Hax_basic.Gcounter.Impl.merge R r d)
+    = Hax_basic.Gcounter.Impl.merge R r a := by
+  mvcgen [Hax_basic.Gcounter.Impl.delta_since_spec, Hax_basic.Gcounter.Impl.merge_spec]
+  congr 1
+  exact Hax_basic.Gcounter.slots_ext R _ _ (by
+    intro i hi; have := h i hi; split <;> simp_all <;> omega)
+"
+    )]
+    // `delta_since_spec` proves this loop with an invariant over the filled prefix.
+    #[allow(clippy::needless_range_loop)]
+    pub fn delta_since(self, since: GCounter<R>) -> GCounter<R> {
+        let mut slots = [crate::new_counter(); R];
+        for i in 0..R {
+            if self.slots[i].0 > since.slots[i].0 {
+                slots[i] = self.slots[i];
+            }
+        }
+        GCounter { slots }
+    }
+
+    /// The total number of updates across all replicas.
+    ///
+    /// # Returns
+    /// The sum of the slots, widened to `u64` so it cannot wrap.
+    ///
+    /// # Properties
+    /// - `a.merge(b).value() >= a.value()`
+    pub fn value(self) -> u64 {
+        let mut total: u64 = 0;
+        for slot in self.slots {
+            total += slot.0 as u64;
+        }
+        total
+    }
+
+    /// Whether every slot of `self` is at most the matching slot of `other`,
+    /// i.e. `other` has seen every update `self` has.
+    ///
+    /// # Properties
+    /// - `a.le(a.merge(b))`
+    /// - `a.le(b) == (a.merge(b) == b)`
+    pub fn le(self, other: GCounter<R>) -> bool {
+        self.slots
+            .iter()
+            .zip(other.slots.iter())
+            .all(|(a, b)| a.0 <= b.0)
+    }
+}
+
+impl<const R: usize> Default for GCounter<R> {
+    fn default() -> GCounter<R> {
+        GCounter::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: [[u32; 3]; 5] = [
+        [0, 0, 0],
+        [1, 0, 7],
+        [3, 9, 2],
+        [u32::MAX, 5, 0],
+        [2, u32::MAX, u32::MAX],
+    ];
+
+    fn g(slots: [u32; 3]) -> GCounter<3> {
+        GCounter::from_slots(slots.map(Counter))
+    }
+
+    #[test]
+    fn test_increment_and_add() {
+        let a = GCounter::<3>::new()
+            .increment(0)
+            .unwrap()
+            .add(2, Counter(5))
+            .unwrap()
+            .increment(0)
+            .unwrap();
+        assert_eq!(a.slots(), [Counter(2), Counter(0), Counter(5)]);
+        assert_eq!(a.slot(2), Counter(5));
+        assert_eq!(a.value(), 7);
+        assert_eq!(
+            g([u32::MAX, 0, 0]).increment(0),
+            Err(CounterError::Overflow {
+                c: Counter::MAX,
+                n: Counter(1)
+            })
+        );
+        assert_eq!(g([u32::MAX; 3]).value(), 3 * u32::MAX as u64);
+    }
+
+    #[test]
+    fn test_merge_laws() {
+        for a in SAMPLES.map(g) {
+            assert_eq!(a.merge(a), a);
+            for b in SAMPLES.map(g) {
+                let ab = a.merge(b);
+                assert_eq!(ab, b.merge(a));
+                assert!(a.le(ab) && b.le(ab));
+                assert!(ab.value() >= a.value());
+                assert_eq!(a.le(b), ab == b);
+                for c in SAMPLES.map(g) {
+                    assert_eq!(a.merge(b.merge(c)), ab.merge(c));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_replicas_converge() {
+        let mut replicas = [GCounter::<3>::new(); 3];
+        for (i, r) in replicas.iter_mut().enumerate() {
+            *r = r.add(i, Counter(10 * (i as u32 + 1))).unwrap();
+        }
+        // Gossip in different orders, with a repeated merge.
+        let x = replicas[0].merge(replicas[1]).merge(replicas[2]);
+        let y = replicas[2]
+            .merge(replicas[0])
+            .merge(replicas[1])
+            .merge(replicas[0]);
+        assert_eq!(x, y);
+        assert_eq!(x.value(), 60);
+    }
+
+    #[test]
+    fn test_delta_since() {
+        for a in SAMPLES.map(g) {
+            assert_eq!(a.delta_since(a), GCounter::new());
+            for s in SAMPLES.map(g) {
+                let d = a.delta_since(s);
+                for r in SAMPLES.map(g).map(|r| r.merge(s)) {
+                    assert_eq!(r.merge(d), r.merge(a));
+                }
+            }
+        }
+        let d = g([4, 1, 9]).delta_since(g([4, 0, 9]));
+        assert_eq!(d.slots(), [Counter(0), Counter(1), Counter(0)]);
+    }
+
+    // With the feature on, `add`'s postcondition is evaluated on the `Err`
+    // path too, after `checked_add` fails inside the body.
+    #[cfg(feature = "runtime-contracts")]
+    #[test]
+    fn test_runtime_contracts_check_overflow_postcondition() {
+        for a in SAMPLES.map(g) {
+            for replica in 0..3 {
+                let headroom = u32::MAX - a.slot(replica).0;
+                assert!(a.add(replica, Counter(headroom)).is_ok());
+                if headroom < u32::MAX {
+                    assert_eq!(
+                        a.add(replica, Counter(headroom + 1)),
+                        Err(CounterError::Overflow {
+                            c: a.slot(replica),
+                            n: Counter(headroom + 1)
+                        })
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Counter operations generic over the primitive unsigned integer widths.
+//!
+//! These are the core counter operations, with the same wrapping semantics
+//! for every width implementing [`CounterValue`]. The functions at the crate
+//! root are these at `T = u32` on the inner value of a
+//! [`Counter`](crate::Counter): `crate::increment(c)` is
+//! `Counter(increment(c.0))`, and so on. The proofs below unfold the instance
+//! for each width; the crate root's `*_eq` lemmas unfold the `u32` instance
+//! once, so proofs about `Counter` never see the trait.
+
+use crate::contracts::contract;
+use hax_lib as hax;
+
+/// A primitive integer usable as a counter value.
+///
+/// Implementations forward to the inherent wrapping operations of the
+/// underlying integer type.
+pub trait CounterValue: Copy + Eq {
+    /// The value of a fresh counter.
+    const ZERO: Self;
+    /// The unit step used by `increment` and `decrement`.
+    const ONE: Self;
+    /// The largest representable value, where `increment` wraps.
+    const MAX: Self;
+
+    /// Addition modulo `2^BITS`.
+    fn wrapping_add(self, n: Self) -> Self;
+    /// Subtraction modulo `2^BITS`.
+    fn wrapping_sub(self, n: Self) -> Self;
+}
+
+macro_rules! impl_counter_value {
+    ($($t:ty),*) => {
+        $(
+            impl CounterValue for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const MAX: Self = <$t>::MAX;
+
+                fn wrapping_add(self, n: Self) -> Self {
+                    <$t>::wrapping_add(self, n)
+                }
+
+                fn wrapping_sub(self, n: Self) -> Self {
+                    <$t>::wrapping_sub(self, n)
+                }
+            }
+        )*
+    };
+}
+
+impl_counter_value!(u8, u16, u32, u64, u128);
+
+contract! {
+    /// Creates a new counter of width `T` initialized to zero.
+    ///
+    /// # Properties
+    /// - `new_counter::<T>() == T::ZERO`
+    ensures(|result| result == T::ZERO)
+    pub fn new_counter<T: CounterValue>() -> T {
+        T::ZERO
+    }
+}
+
+contract! {
+    /// Increments a counter by one, wrapping at `T::MAX`.
+    ///
+    /// # Properties
+    /// - `increment(T::MAX) == T::ZERO`
+    /// - `increment::<u32>(c) == crate::increment(Counter(c)).0`
+    #[hax::lean::after(
+        "-- Per-width specifications of increment
+theorem Hax_basic.Generic.increment_spec_u8 (c : u8) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.increment u8 c) ⦃ ⇓ result => ⌜ result = c + 1 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.increment, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl, Core.Num.Impl_6.wrapping_add]
+
+theorem Hax_basic.Generic.increment_spec_u16 (c : u16) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.increment u16 c) ⦃ ⇓ result => ⌜ result = c + 1 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.increment, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl_1, Core.Num.Impl_7.wrapping_add]
+
+theorem Hax_basic.Generic.increment_spec_u32 (c : u32) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.increment u32 c) ⦃ ⇓ result => ⌜ result = c + 1 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.increment, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl_2, Core.Num.Impl_8.wrapping_add]
+
+theorem Hax_basic.Generic.increment_spec_u64 (c : u64) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.increment u64 c) ⦃ ⇓ result => ⌜ result = c + 1 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.increment, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl_3, Core.Num.Impl_9.wrapping_add]
+
+theorem Hax_basic.Generic.increment_spec_u128 (c : u128) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.increment u128 c) ⦃ ⇓ result => ⌜ result = c + 1 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.increment, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl_4, Core.Num.Impl_10.wrapping_add]
+"
+    )]
+    ensures(|result| result == c.wrapping_add(T::ONE))
+    pub fn increment<T: CounterValue>(c: T) -> T {
+        c.wrapping_add(T::ONE)
+    }
+}
+
+contract! {
+    /// Decrements a counter by one, wrapping at zero.
+    ///
+    /// # Properties
+    /// - `decrement(T::ZERO) == T::MAX`
+    /// - `decrement(increment(c)) == c`
+    /// - `decrement::<u32>(c) == crate::decrement(Counter(c)).0`
+    #[hax::lean::after(
+        "-- Per-width specifications of decrement
+theorem Hax_basic.Generic.decrement_spec_u8 (c : u8) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.decrement u8 c) ⦃ ⇓ result => ⌜ result = c - 1 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.decrement, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl, Core.Num.Impl_6.wrapping_sub]
+
+theorem Hax_basic.Generic.decrement_spec_u16 (c : u16) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.decrement u16 c) ⦃ ⇓ result => ⌜ result = c - 1 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.decrement, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl_1, Core.Num.Impl_7.wrapping_sub]
+
+theorem Hax_basic.Generic.decrement_spec_u32 (c : u32) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.decrement u32 c) ⦃ ⇓ result => ⌜ result = c - 1 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.decrement, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl_2, Core.Num.Impl_8.wrapping_sub]
+
+theorem Hax_basic.Generic.decrement_spec_u64 (c : u64) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.decrement u64 c) ⦃ ⇓ result => ⌜ result = c - 1 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.decrement, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl_3, Core.Num.Impl_9.wrapping_sub]
+
+theorem Hax_basic.Generic.decrement_spec_u128 (c : u128) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.decrement u128 c) ⦃ ⇓ result => ⌜ result = c - 1 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.decrement, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl_4, Core.Num.Impl_10.wrapping_sub]
+"
+    )]
+    ensures(|result| result == c.wrapping_sub(T::ONE))
+    pub fn decrement<T: CounterValue>(c: T) -> T {
+        c.wrapping_sub(T::ONE)
+    }
+}
+
+contract! {
+    /// Adds a value to the counter, wrapping on overflow.
+    ///
+    /// # Properties
+    /// - `add(c, T::ZERO) == c`
+    /// - `add(c, T::ONE) == increment(c)`
+    /// - `add::<u32>(c, n) == crate::add(Counter(c), Counter(n)).0`
+    #[hax::lean::after(
+        "-- Per-width specifications of add
+theorem Hax_basic.Generic.add_spec_u8 (c n : u8) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.add u8 c n) ⦃ ⇓ result => ⌜ result = c + n ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.add, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.Impl, Core.Num.Impl_6.wrapping_add]
+
+theorem Hax_basic.Generic.add_spec_u16 (c n : u16) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.add u16 c n) ⦃ ⇓ result => ⌜ result = c + n ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.add, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.Impl_1, Core.Num.Impl_7.wrapping_add]
+
+theorem Hax_basic.Generic.add_spec_u32 (c n : u32) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.add u32 c n) ⦃ ⇓ result => ⌜ result = c + n ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.add, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.Impl_2, Core.Num.Impl_8.wrapping_add]
+
+theorem Hax_basic.Generic.add_spec_u64 (c n : u64) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.add u64 c n) ⦃ ⇓ result => ⌜ result = c + n ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.add, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.Impl_3, Core.Num.Impl_9.wrapping_add]
+
+theorem Hax_basic.Generic.add_spec_u128 (c n : u128) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.add u128 c n) ⦃ ⇓ result => ⌜ result = c + n ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.add, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.Impl_4, Core.Num.Impl_10.wrapping_add]
+"
+    )]
+    ensures(|result| result == c.wrapping_add(n))
+    pub fn add<T: CounterValue>(c: T, n: T) -> T {
+        c.wrapping_add(n)
+    }
+}
+
+contract! {
+    /// Subtracts a value from the counter, wrapping on underflow.
+    ///
+    /// # Properties
+    /// - `subtract(c, T::ZERO) == c`
+    /// - `subtract(c, T::ONE) == decrement(c)`
+    /// - `subtract::<u32>(c, n) == crate::subtract(Counter(c), Counter(n)).0`
+    #[hax::lean::after(
+        "-- Per-width specifications of subtract
+theorem Hax_basic.Generic.subtract_spec_u8 (c n : u8) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.subtract u8 c n) ⦃ ⇓ result => ⌜ result = c - n ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.subtract, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.Impl, Core.Num.Impl_6.wrapping_sub]
+
+theorem Hax_basic.Generic.subtract_spec_u16 (c n : u16) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.subtract u16 c n) ⦃ ⇓ result => ⌜ result = c - n ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.subtract, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.Impl_1, Core.Num.Impl_7.wrapping_sub]
+
+theorem Hax_basic.Generic.subtract_spec_u32 (c n : u32) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.subtract u32 c n) ⦃ ⇓ result => ⌜ result = c - n ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.subtract, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.Impl_2, Core.Num.Impl_8.wrapping_sub]
+
+theorem Hax_basic.Generic.subtract_spec_u64 (c n : u64) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.subtract u64 c n) ⦃ ⇓ result => ⌜ result = c - n ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.subtract, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.Impl_3, Core.Num.Impl_9.wrapping_sub]
+
+theorem Hax_basic.Generic.subtract_spec_u128 (c n : u128) :
+  ⦃ ⌜ True ⌝ ⦄ (Hax_basic.Generic.subtract u128 c n) ⦃ ⇓ result => ⌜ result = c - n ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Generic.subtract, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.Impl_4, Core.Num.Impl_10.wrapping_sub]
+"
+    )]
+    ensures(|result| result == c.wrapping_sub(n))
+    pub fn subtract<T: CounterValue>(c: T, n: T) -> T {
+        c.wrapping_sub(n)
+    }
+}
+
+contract! {
+    /// Resets the counter to zero.
+    ///
+    /// # Properties
+    /// - `reset(c) == new_counter()`
+    ensures(|result| result == T::ZERO)
+    pub fn reset<T: CounterValue>(_c: T) -> T {
+        T::ZERO
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Counter;
+
+    #[test]
+    fn test_new_counter_and_reset() {
+        assert_eq!(new_counter::<u8>(), 0);
+        assert_eq!(new_counter::<u128>(), 0);
+        assert_eq!(reset(200u8), new_counter());
+        assert_eq!(reset(u64::MAX), 0);
+    }
+
+    #[test]
+    fn test_u32_matches_core() {
+        for c in [0, 1, 42, u32::MAX - 1, u32::MAX] {
+            assert_eq!(increment(c), crate::increment(Counter(c)).0);
+            assert_eq!(decrement(c), crate::decrement(Counter(c)).0);
+            for n in [0, 1, 7, u32::MAX] {
+                assert_eq!(add(c, n), crate::add(Counter(c), Counter(n)).0);
+                assert_eq!(subtract(c, n), crate::subtract(Counter(c), Counter(n)).0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_u8_wrapping() {
+        assert_eq!(increment(u8::MAX), 0);
+        assert_eq!(decrement(0u8), u8::MAX);
+        assert_eq!(add(250u8, 10), 4);
+        assert_eq!(subtract(4u8, 10), 250);
+    }
+
+    #[test]
+    fn test_wide_widths() {
+        assert_eq!(increment(u32::MAX as u64), 1 << 32);
+        assert_eq!(increment(u64::MAX), 0);
+        assert_eq!(add(u128::MAX, 2), 1);
+        assert_eq!(decrement(increment(12345u16)), 12345);
+    }
+}
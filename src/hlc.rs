@@ -0,0 +1,375 @@
+//! A hybrid logical clock.
+//!
+//! A [`Timestamp`] pairs a physical time (milliseconds, as read from a
+//! [`PhysicalClock`]) with a [`Counter`] logical component that orders events
+//! sharing the same physical time. Timestamps stay close to wall time, but
+//! like a Lamport clock they are strictly increasing at each node and later
+//! than every timestamp received.
+//!
+//! The core is pure: [`Timestamp::now`] and [`Timestamp::update`] take the
+//! physical reading as an argument. [`HybridClock`] reads it from an
+//! injectable source, so tests can drive it with a fake clock.
+//!
+//! A timestamp encodes into one `u64`: the physical time in the high
+//! [`PHYSICAL_BITS`] bits and the logical component in the low
+//! [`LOGICAL_BITS`]. When the logical component would pass [`LOGICAL_MAX`] it
+//! carries into the physical time, which then runs ahead of the wall clock
+//! by one millisecond until the wall clock catches up.
+
+use crate::contracts::contract;
+use crate::Counter;
+use hax_lib as hax;
+
+/// Bits of the encoding holding the logical component.
+pub const LOGICAL_BITS: u32 = 16;
+
+/// Bits of the encoding holding the physical time.
+pub const PHYSICAL_BITS: u32 = 64 - LOGICAL_BITS;
+
+/// The largest logical component; one more carries into the physical time.
+pub const LOGICAL_MAX: Counter = Counter((1 << LOGICAL_BITS) - 1);
+
+/// The largest physical time. Readings above it are clamped to it.
+pub const PHYSICAL_MAX: u64 = (1 << PHYSICAL_BITS) - 1;
+
+/// A source of physical time in milliseconds.
+pub trait PhysicalClock {
+    /// The current physical time.
+    fn now(&self) -> u64;
+}
+
+/// Milliseconds since the Unix epoch, from the system clock.
+#[hax::exclude]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+#[hax::exclude]
+impl PhysicalClock for SystemClock {
+    fn now(&self) -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64)
+    }
+}
+
+/// A hybrid logical clock reading.
+///
+/// Timestamps order by physical time, then by logical component, which is
+/// also the order of their encodings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    physical: u64,
+    logical: Counter,
+}
+
+impl Timestamp {
+    /// The latest timestamp. A clock that reaches it stays there.
+    pub const MAX: Timestamp = Timestamp {
+        physical: PHYSICAL_MAX,
+        logical: LOGICAL_MAX,
+    };
+
+    /// Creates a timestamp.
+    ///
+    /// # Returns
+    /// `None` unless `physical <= PHYSICAL_MAX` and `logical <= LOGICAL_MAX`.
+    pub fn new(physical: u64, logical: Counter) -> Option<Timestamp> {
+        if physical <= PHYSICAL_MAX && logical.0 <= LOGICAL_MAX.0 {
+            Some(Timestamp { physical, logical })
+        } else {
+            None
+        }
+    }
+
+    /// The physical time.
+    pub fn physical(self) -> u64 {
+        self.physical
+    }
+
+    /// The logical component.
+    pub fn logical(self) -> Counter {
+        self.logical
+    }
+
+    /// Packs the timestamp into one `u64`.
+    ///
+    /// # Properties
+    /// - `Timestamp::decode(t.encode()) == t`
+    /// - `a < b` exactly when `a.encode() < b.encode()`
+    #[hax::lean::after(
+        "-- The encoding is order-preserving
+theorem Hax_basic.Hlc.Impl.encode_lt (a b : Hax_basic.Hlc.Timestamp)
+  (ha : a.physical < 281474976710656) (hb : b.physical < 281474976710656)
+  (hla : a.logical._0 < 65536) (hlb : b.logical._0 < 65536)
+  (h : a.physical < b.physical ∨ (a.physical = b.physical ∧ a.logical._0 < b.logical._0)) :
+  ⦃ ⌜ True ⌝ ⦄
+  (do let x ← Hax_basic.Hlc.Impl.encode a; let y ← Hax_basic.Hlc.Impl.encode b; pure (x, y))
+  ⦃ ⇓ r => ⌜ r.1 < r.2 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Hlc.Impl.encode]
+  all_goals bv_decide
+"
+    )]
+    pub fn encode(self) -> u64 {
+        (self.physical << LOGICAL_BITS) | self.logical.0 as u64
+    }
+
+    /// Unpacks a timestamp from [`Timestamp::encode`].
+    pub fn decode(encoded: u64) -> Timestamp {
+        Timestamp {
+            physical: encoded >> LOGICAL_BITS,
+            logical: Counter((encoded & LOGICAL_MAX.0 as u64) as u32),
+        }
+    }
+
+    /// The timestamp immediately after `self`.
+    ///
+    /// # Returns
+    /// `self` with its logical component incremented, or the next physical
+    /// time with a zero logical component when it is at `LOGICAL_MAX`.
+    /// `Timestamp::MAX` is its own successor.
+    fn successor(self) -> Timestamp {
+        if self.logical.0 < LOGICAL_MAX.0 {
+            Timestamp {
+                physical: self.physical,
+                logical: crate::increment(self.logical),
+            }
+        } else if self.physical < PHYSICAL_MAX {
+            Timestamp {
+                physical: self.physical + 1,
+                logical: crate::new_counter(),
+            }
+        } else {
+            self
+        }
+    }
+
+    contract! {
+        /// The timestamp of a local or send event, given the last timestamp
+        /// `self` and a physical reading.
+        ///
+        /// # Returns
+        /// `(physical, 0)` when the reading is past `self.physical()`, otherwise
+        /// the successor of `self`.
+        ///
+        /// # Properties
+        /// - `t.now(p) > t` when `t < Timestamp::MAX`
+        /// - `t.now(p).physical() >= min(p, PHYSICAL_MAX)`
+        #[hax::lean::after(
+            "-- A local event is strictly later than the previous one, below MAX
+theorem Hax_basic.Hlc.Impl.now_later (t : Hax_basic.Hlc.Timestamp) (physical : u64)
+  (hp : t.physical < 281474976710655) (hl : t.logical._0 ≤ 65535) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Hlc.Impl.now t physical)
+  ⦃ ⇓ r => ⌜ t.physical < r.physical ∨ (t.physical = r.physical ∧ t.logical._0 < r.logical._0) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Hlc.Impl.now, Hax_basic.Hlc.Impl.successor, Hax_basic.Hlc.min_physical,
+    Hax_basic.increment_eq, Core.Num.Impl_8.wrapping_add]
+  all_goals (simp_all; try omega)
+"
+        )]
+        requires(self.physical <= PHYSICAL_MAX && self.logical.0 <= LOGICAL_MAX.0)
+        ensures(|result| result >= self && result.physical >= min_physical(physical))
+        pub fn now(self, physical: u64) -> Timestamp {
+            let physical = min_physical(physical);
+            if physical > self.physical {
+                Timestamp {
+                    physical,
+                    logical: crate::new_counter(),
+                }
+            } else {
+                self.successor()
+            }
+        }
+    }
+
+    contract! {
+        /// The timestamp of receiving a message stamped `remote`, given the last
+        /// timestamp `self` and a physical reading.
+        ///
+        /// # Returns
+        /// `(physical, 0)` when the reading is past both timestamps, otherwise
+        /// the successor of the later of `self` and `remote`.
+        ///
+        /// # Properties
+        /// - `t.update(r, p) > t` and `t.update(r, p) > r` when both are below
+        ///   `Timestamp::MAX`
+        #[hax::lean::after(
+            "-- A receive event is strictly later than the previous local event and
+-- the send event, below MAX
+theorem Hax_basic.Hlc.Impl.update_later (t remote : Hax_basic.Hlc.Timestamp) (physical : u64)
+  (hp : t.physical < 281474976710655) (hl : t.logical._0 ≤ 65535)
+  (hrp : remote.physical < 281474976710655) (hrl : remote.logical._0 ≤ 65535) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Hlc.Impl.update t remote physical)
+  ⦃ ⇓ r => ⌜ (t.physical < r.physical ∨ (t.physical = r.physical ∧ t.logical._0 < r.logical._0))
+      ∧ (remote.physical < r.physical
+        ∨ (remote.physical = r.physical ∧ remote.logical._0 < r.logical._0)) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Hlc.Impl.update, Hax_basic.Hlc.Impl.now, Hax_basic.Hlc.Impl.successor,
+    Hax_basic.Hlc.later, Hax_basic.Hlc.min_physical, Hax_basic.increment_eq,
+    Core.Num.Impl_8.wrapping_add]
+  all_goals (simp_all; try omega)
+"
+        )]
+        requires(self.physical <= PHYSICAL_MAX && self.logical.0 <= LOGICAL_MAX.0
+            && remote.physical <= PHYSICAL_MAX && remote.logical.0 <= LOGICAL_MAX.0)
+        ensures(|result| result >= self && result >= remote)
+        pub fn update(self, remote: Timestamp, physical: u64) -> Timestamp {
+            later(self, remote).now(physical)
+        }
+    }
+}
+
+/// A physical reading clamped to `PHYSICAL_MAX`.
+fn min_physical(physical: u64) -> u64 {
+    if physical > PHYSICAL_MAX {
+        PHYSICAL_MAX
+    } else {
+        physical
+    }
+}
+
+/// The later of two timestamps.
+fn later(a: Timestamp, b: Timestamp) -> Timestamp {
+    if a.physical > b.physical || (a.physical == b.physical && a.logical.0 >= b.logical.0) {
+        a
+    } else {
+        b
+    }
+}
+
+/// A hybrid logical clock reading physical time from `C`.
+#[derive(Debug, Clone, Default)]
+pub struct HybridClock<C: PhysicalClock> {
+    source: C,
+    last: Timestamp,
+}
+
+impl<C: PhysicalClock> HybridClock<C> {
+    /// Creates a clock that has issued no timestamp yet.
+    pub fn new(source: C) -> HybridClock<C> {
+        HybridClock::starting_at(source, Timestamp::default())
+    }
+
+    /// Creates a clock whose last timestamp was `last`, for example one
+    /// restored from storage.
+    pub fn starting_at(source: C, last: Timestamp) -> HybridClock<C> {
+        HybridClock { source, last }
+    }
+
+    /// The physical time source.
+    pub fn source(&self) -> &C {
+        &self.source
+    }
+
+    /// The last timestamp issued.
+    pub fn last(&self) -> Timestamp {
+        self.last
+    }
+
+    /// Timestamps a local or send event.
+    pub fn now(&mut self) -> Timestamp {
+        self.last = self.last.now(self.source.now());
+        self.last
+    }
+
+    /// Timestamps receiving a message stamped `remote`.
+    pub fn update(&mut self, remote: Timestamp) -> Timestamp {
+        self.last = self.last.update(remote, self.source.now());
+        self.last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    /// A clock the test sets by hand.
+    #[derive(Default)]
+    struct FakeClock(Cell<u64>);
+
+    impl FakeClock {
+        fn set(&self, millis: u64) {
+            self.0.set(millis);
+        }
+    }
+
+    impl PhysicalClock for FakeClock {
+        fn now(&self) -> u64 {
+            self.0.get()
+        }
+    }
+
+    fn ts(physical: u64, logical: u32) -> Timestamp {
+        Timestamp::new(physical, Counter(logical)).unwrap()
+    }
+
+    #[test]
+    fn test_now_follows_the_physical_clock() {
+        let mut hlc = HybridClock::new(FakeClock::default());
+        hlc.source().set(100);
+        assert_eq!(hlc.now(), ts(100, 0));
+        assert_eq!(hlc.now(), ts(100, 1));
+        // The physical clock going backwards does not move the clock back.
+        hlc.source().set(90);
+        assert_eq!(hlc.now(), ts(100, 2));
+        hlc.source().set(101);
+        assert_eq!(hlc.now(), ts(101, 0));
+    }
+
+    #[test]
+    fn test_update_is_later_than_remote() {
+        let mut hlc = HybridClock::new(FakeClock::default());
+        hlc.source().set(100);
+        hlc.now();
+        // A remote clock running ahead pulls this one forward.
+        assert_eq!(hlc.update(ts(150, 7)), ts(150, 8));
+        // A remote timestamp in the past only advances the logical component.
+        assert_eq!(hlc.update(ts(120, 3)), ts(150, 9));
+        // Equal physical times take the larger logical component.
+        assert_eq!(hlc.update(ts(150, 20)), ts(150, 21));
+        hlc.source().set(200);
+        assert_eq!(hlc.update(ts(150, 40)), ts(200, 0));
+    }
+
+    #[test]
+    fn test_logical_overflow_carries() {
+        let t = ts(100, LOGICAL_MAX.0);
+        assert_eq!(t.now(100), ts(101, 0));
+        assert_eq!(t.update(ts(50, 0), 0), ts(101, 0));
+        assert_eq!(Timestamp::MAX.now(u64::MAX), Timestamp::MAX);
+        assert_eq!(Timestamp::default().now(u64::MAX), ts(PHYSICAL_MAX, 0));
+        assert!(Timestamp::new(0, Counter(LOGICAL_MAX.0 + 1)).is_none());
+        assert!(Timestamp::new(PHYSICAL_MAX + 1, Counter(0)).is_none());
+    }
+
+    #[test]
+    fn test_encoding_round_trips_and_preserves_order() {
+        let samples = [
+            ts(0, 0),
+            ts(0, 1),
+            ts(0, LOGICAL_MAX.0),
+            ts(1, 0),
+            ts(1_700_000_000_000, 42),
+            Timestamp::MAX,
+        ];
+        for a in samples {
+            assert_eq!(Timestamp::decode(a.encode()), a);
+            for b in samples {
+                assert_eq!(a < b, a.encode() < b.encode());
+            }
+        }
+        assert_eq!(ts(1, 2).encode(), 0x1_0002);
+        assert_eq!(Timestamp::MAX.encode(), u64::MAX);
+    }
+
+    #[test]
+    fn test_system_clock() {
+        let mut hlc = HybridClock::new(SystemClock);
+        let a = hlc.now();
+        let b = hlc.now();
+        assert!(a < b && a.physical() > 0);
+    }
+}
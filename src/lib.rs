@@ -1,119 +1,811 @@
-/// A simple counter module written in functional style for formal verification.
-/// 
-/// This module provides pure functions for counter operations that are
-/// amenable to formal verification techniques.
+//! A simple counter module written in functional style for formal verification.
+//! 
+//! This module provides pure functions for counter operations that are
+//! amenable to formal verification techniques.
+
+use core::fmt;
+use core::num::ParseIntError;
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+use core::str::FromStr;
 
 use hax_lib as hax;
 
-/// Represents a counter value.
-pub type Counter = u32;
+pub mod atomic;
+pub mod bounded;
+pub mod clock;
+mod contracts;
+pub mod gcounter;
+pub mod generic;
+pub mod hlc;
+pub mod modular;
+pub mod monotonic;
+pub mod nonce;
+pub mod op;
+pub mod pncounter;
+pub mod rate;
+pub mod serial;
+pub mod signed;
+pub mod simplify;
+pub mod state;
+pub mod typed;
+pub mod wide;
+pub mod wrap_tracking;
+
+use contracts::contract;
 
-/// Creates a new counter initialized to zero.
+/// Represents a counter value.
 /// 
-/// # Returns
-/// A counter value of 0.
+/// A newtype over `u32`, so counters cannot be mixed up with unrelated
+/// integers. As with `std::num::Wrapping`, the inner value is public and the
+/// arithmetic operators wrap: `c + n` is `add(c, n)` and `c - n` is
+/// `subtract(c, n)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Counter(pub u32);
+
+impl Counter {
+    /// The largest counter value; `increment(Counter::MAX) == new_counter()`.
+    pub const MAX: Counter = Counter(u32::MAX);
+}
+
+/// Errors reported by the checked counter operations.
 /// 
-/// # Properties
-/// - `new_counter() == 0`
-#[hax::ensures(|result| result == 0)]
-#[hax::lean::before("@[simp, spec]")]
-#[hax::lean::after(
-    "-- Specification of new_counter
+/// Each variant carries the operands of the operation that failed so callers
+/// can report exactly which update was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterError {
+    /// `c + n` would exceed `Counter::MAX`.
+    Overflow { c: Counter, n: Counter },
+    /// `c - n` would go below zero.
+    Underflow { c: Counter, n: Counter },
+}
+
+contract! {
+    /// Creates a new counter initialized to zero.
+    /// 
+    /// # Returns
+    /// A counter value of 0.
+    /// 
+    /// # Properties
+    /// - `new_counter() == 0`
+    #[hax::lean::after(
+        "-- new_counter is the generic new_counter at u32
+@[simp] theorem Hax_basic.new_counter_eq :
+  Hax_basic.new_counter Rust_primitives.Hax.Tuple0.mk = pure ⟨0⟩ := by
+  simp [Hax_basic.new_counter, Hax_basic.Generic.new_counter, Hax_basic.Generic.CounterValue.ZERO,
+    Hax_basic.Generic.Impl_2]
+
+-- Specification of new_counter
 theorem Hax_basic.new_counter_spec :
   ⦃ ⌜ True ⌝ ⦄ -- Precondition (always true here)
   (Hax_basic.new_counter Rust_primitives.Hax.Tuple0.mk) -- The function call
   ⦃ ⇓ result => ⌜ Hax_basic._.ensures Rust_primitives.Hax.Tuple0.mk result = pure true ⌝ ⦄  -- Postcondition
   := by
-  mvcgen [Hax_basic.new_counter, Hax_basic._.ensures]
+  mvcgen [Hax_basic.new_counter_eq, Hax_basic._.ensures]
 "
-)]
-pub fn new_counter() -> Counter {
-    0
+    )]
+    ensures(|result| result.0 == 0)
+    pub fn new_counter() -> Counter {
+        Counter(generic::new_counter())
+    }
 }
 
-/// Increments a counter by one.
-/// 
-/// # Arguments
-/// * `c` - The current counter value
-/// 
-/// # Returns
-/// The counter value incremented by 1.
-/// 
-/// # Properties
-/// - `increment(new_counter()) == 1`
-/// - `increment(increment(c)) == increment(c) + 1`
-/// - `increment(c) == c + 1`
-/// TODO #[hax::ensures(|result| result == c.wrapping_add(1))]
-pub fn increment(c: Counter) -> Counter {
-    c.wrapping_add(1)
+contract! {
+    /// Increments a counter by one.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// 
+    /// # Returns
+    /// The counter value incremented by 1.
+    /// 
+    /// # Properties
+    /// - `increment(new_counter()) == 1`
+    /// - `increment(increment(c)) == increment(c) + 1`
+    /// - `increment(c) == c + 1`
+    #[hax::lean::after(
+        "-- increment is the generic increment at u32
+@[simp] theorem Hax_basic.increment_eq (c : Hax_basic.Counter) :
+  Hax_basic.increment c = pure ⟨c._0 + 1⟩ := by
+  simp [Hax_basic.increment, Hax_basic.Generic.increment, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl_2,
+    Core.Num.Impl_8.wrapping_add]
+
+-- Specification of increment
+theorem Hax_basic.increment_spec (c : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄ -- Precondition (always true here)
+  (Hax_basic.increment c) -- The function call
+  ⦃ ⇓ result => ⌜ result._0 = c._0 + 1 ⌝ ⦄  -- Postcondition
+  := by
+  mvcgen [Hax_basic.increment_eq, Core.Num.Impl_8.wrapping_add]
+
+-- Law: two increments add two
+theorem Hax_basic.increment_increment (c : Hax_basic.Counter) :
+  (do let r ← Hax_basic.increment c; Hax_basic.increment r) = pure ⟨c._0 + 2⟩ := by
+  simp [Hax_basic.increment_eq, Core.Num.Impl_8.wrapping_add]
+  grind
+"
+    )]
+    ensures(|result| result.0 == c.0.wrapping_add(1))
+    pub fn increment(c: Counter) -> Counter {
+        Counter(generic::increment(c.0))
+    }
 }
 
-/// Decrements a counter by one.
-/// 
-/// # Arguments
-/// * `c` - The current counter value
-/// 
-/// # Returns
-/// The counter value decremented by 1 (wraps around on underflow).
-/// 
-/// # Properties
-/// - `decrement(increment(c)) == c` (when no overflow occurs)
-/// - `decrement(new_counter()) == u32::MAX`
-/// TODO #[hax::ensures(|result| result == c.wrapping_sub(1))]
-pub fn decrement(c: Counter) -> Counter {
-    c.wrapping_sub(1)
+contract! {
+    /// Decrements a counter by one.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// 
+    /// # Returns
+    /// The counter value decremented by 1 (wraps around on underflow).
+    /// 
+    /// # Properties
+    /// - `decrement(increment(c)) == c` (when no overflow occurs)
+    /// - `decrement(new_counter()) == Counter::MAX`
+    #[hax::lean::after(
+        "-- decrement is the generic decrement at u32
+@[simp] theorem Hax_basic.decrement_eq (c : Hax_basic.Counter) :
+  Hax_basic.decrement c = pure ⟨c._0 - 1⟩ := by
+  simp [Hax_basic.decrement, Hax_basic.Generic.decrement, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.CounterValue.ONE, Hax_basic.Generic.Impl_2,
+    Core.Num.Impl_8.wrapping_sub]
+
+-- Specification of decrement
+theorem Hax_basic.decrement_spec (c : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄ -- Precondition (always true here)
+  (Hax_basic.decrement c) -- The function call
+  ⦃ ⇓ result => ⌜ result._0 = c._0 - 1 ⌝ ⦄  -- Postcondition
+  := by
+  mvcgen [Hax_basic.decrement_eq, Core.Num.Impl_8.wrapping_sub]
+
+-- Law: decrement undoes increment (modulo 2^32)
+theorem Hax_basic.increment_decrement_inverse (c : Hax_basic.Counter) :
+  (do let r ← Hax_basic.increment c; Hax_basic.decrement r) = pure c := by
+  simp [Hax_basic.increment_eq, Hax_basic.decrement_eq, Core.Num.Impl_8.wrapping_add, Core.Num.Impl_8.wrapping_sub]
+
+-- Law: increment undoes decrement (modulo 2^32)
+theorem Hax_basic.decrement_increment_inverse (c : Hax_basic.Counter) :
+  (do let r ← Hax_basic.decrement c; Hax_basic.increment r) = pure c := by
+  simp [Hax_basic.increment_eq, Hax_basic.decrement_eq, Core.Num.Impl_8.wrapping_add, Core.Num.Impl_8.wrapping_sub]
+"
+    )]
+    ensures(|result| result.0 == c.0.wrapping_sub(1))
+    pub fn decrement(c: Counter) -> Counter {
+        Counter(generic::decrement(c.0))
+    }
 }
 
-/// Adds a value to the counter.
-/// 
-/// # Arguments
-/// * `c` - The current counter value
-/// * `n` - The value to add
-/// 
-/// # Returns
-/// The counter value with `n` added (wraps around on overflow).
-/// 
-/// # Properties
-/// - `add(c, 0) == c`
-/// - `add(c, 1) == increment(c)`
-/// - `add(add(c, n), m) == add(c, n + m)` (when no overflow)
-/// TODO #[hax::ensures(|result| result == c.wrapping_add(n))]
-pub fn add(c: Counter, n: Counter) -> Counter {
-    c.wrapping_add(n)
+contract! {
+    /// Adds a value to the counter.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// * `n` - The value to add
+    /// 
+    /// # Returns
+    /// The counter value with `n` added (wraps around on overflow).
+    /// 
+    /// # Properties
+    /// - `add(c, 0) == c`
+    /// - `add(c, 1) == increment(c)`
+    /// - `add(add(c, n), m) == add(c, n + m)` (when no overflow)
+    #[hax::lean::after(
+        "-- add is the generic add at u32
+@[simp] theorem Hax_basic.add_eq (c n : Hax_basic.Counter) :
+  Hax_basic.add c n = pure ⟨c._0 + n._0⟩ := by
+  simp [Hax_basic.add, Hax_basic.Generic.add, Hax_basic.Generic.CounterValue.wrapping_add,
+    Hax_basic.Generic.Impl_2, Core.Num.Impl_8.wrapping_add]
+
+-- Specification of add
+theorem Hax_basic.add_spec (c n : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄ -- Precondition (always true here)
+  (Hax_basic.add c n) -- The function call
+  ⦃ ⇓ result => ⌜ result._0 = c._0 + n._0 ⌝ ⦄  -- Postcondition
+  := by
+  mvcgen [Hax_basic.add_eq, Core.Num.Impl_8.wrapping_add]
+
+-- Law: zero is the identity of add
+theorem Hax_basic.add_zero (c : Hax_basic.Counter) : Hax_basic.add c ⟨0⟩ = pure c := by
+  simp [Hax_basic.add_eq, Core.Num.Impl_8.wrapping_add]
+
+-- Law: adding one is increment
+theorem Hax_basic.add_one_eq_increment (c : Hax_basic.Counter) :
+  Hax_basic.add c ⟨1⟩ = Hax_basic.increment c := by
+  simp [Hax_basic.add_eq, Hax_basic.increment_eq]
+
+-- Law: add is commutative
+theorem Hax_basic.add_comm (c n : Hax_basic.Counter) : Hax_basic.add c n = Hax_basic.add n c := by
+  simp [Hax_basic.add_eq, Core.Num.Impl_8.wrapping_add]
+  grind
+
+-- Law: add is associative modulo 2^32
+theorem Hax_basic.add_assoc_mod (c n m : Hax_basic.Counter) :
+  (do let r ← Hax_basic.add c n; Hax_basic.add r m)
+    = (do let s ← Hax_basic.add n m; Hax_basic.add c s) := by
+  simp [Hax_basic.add_eq, Core.Num.Impl_8.wrapping_add]
+  grind
+
+-- Law: add(c, n) is n successive increments
+theorem Hax_basic.add_eq_increments (c : Hax_basic.Counter) (n : Nat) :
+  Hax_basic.add c ⟨UInt32.ofNat n⟩ = Nat.repeat (· >>= Hax_basic.increment) n (pure c) := by
+  induction n with
+  | zero => simp [Hax_basic.add_eq, Core.Num.Impl_8.wrapping_add, Nat.repeat]
+  | succ n ih =>
+    simp only [Nat.repeat, ← ih]
+    simp [Hax_basic.add_eq, Hax_basic.increment_eq, Core.Num.Impl_8.wrapping_add]
+    grind
+"
+    )]
+    ensures(|result| result.0 == c.0.wrapping_add(n.0))
+    pub fn add(c: Counter, n: Counter) -> Counter {
+        Counter(generic::add(c.0, n.0))
+    }
 }
 
-/// Subtracts a value from the counter.
-/// 
-/// # Arguments
-/// * `c` - The current counter value
-/// * `n` - The value to subtract
-/// 
-/// # Returns
-/// The counter value with `n` subtracted (wraps around on underflow).
-/// 
-/// # Properties
-/// - `subtract(c, 0) == c`
-/// - `subtract(c, 1) == decrement(c)`
-/// - `subtract(subtract(c, n), m) == subtract(c, n + m)` (when no underflow)
-/// TODO #[hax::ensures(|result| result == c.wrapping_sub(n))]
-pub fn subtract(c: Counter, n: Counter) -> Counter {
-    c.wrapping_sub(n)
+contract! {
+    /// Subtracts a value from the counter.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// * `n` - The value to subtract
+    /// 
+    /// # Returns
+    /// The counter value with `n` subtracted (wraps around on underflow).
+    /// 
+    /// # Properties
+    /// - `subtract(c, 0) == c`
+    /// - `subtract(c, 1) == decrement(c)`
+    /// - `subtract(subtract(c, n), m) == subtract(c, n + m)` (when no underflow)
+    #[hax::lean::after(
+        "-- subtract is the generic subtract at u32
+@[simp] theorem Hax_basic.subtract_eq (c n : Hax_basic.Counter) :
+  Hax_basic.subtract c n = pure ⟨c._0 - n._0⟩ := by
+  simp [Hax_basic.subtract, Hax_basic.Generic.subtract, Hax_basic.Generic.CounterValue.wrapping_sub,
+    Hax_basic.Generic.Impl_2, Core.Num.Impl_8.wrapping_sub]
+
+-- Specification of subtract
+theorem Hax_basic.subtract_spec (c n : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄ -- Precondition (always true here)
+  (Hax_basic.subtract c n) -- The function call
+  ⦃ ⇓ result => ⌜ result._0 = c._0 - n._0 ⌝ ⦄  -- Postcondition
+  := by
+  mvcgen [Hax_basic.subtract_eq, Core.Num.Impl_8.wrapping_sub]
+
+-- Law: zero is the right identity of subtract
+theorem Hax_basic.subtract_zero (c : Hax_basic.Counter) : Hax_basic.subtract c ⟨0⟩ = pure c := by
+  simp [Hax_basic.subtract_eq, Core.Num.Impl_8.wrapping_sub]
+
+-- Law: subtracting one is decrement
+theorem Hax_basic.subtract_one_eq_decrement (c : Hax_basic.Counter) :
+  Hax_basic.subtract c ⟨1⟩ = Hax_basic.decrement c := by
+  simp [Hax_basic.subtract_eq, Hax_basic.decrement_eq]
+
+-- Law: subtract undoes add (modulo 2^32)
+theorem Hax_basic.add_subtract_inverse (c n : Hax_basic.Counter) :
+  (do let r ← Hax_basic.add c n; Hax_basic.subtract r n) = pure c := by
+  simp [Hax_basic.add_eq, Hax_basic.subtract_eq, Core.Num.Impl_8.wrapping_add, Core.Num.Impl_8.wrapping_sub]
+
+-- Law: successive subtractions subtract the sum (modulo 2^32)
+theorem Hax_basic.subtract_subtract_mod (c n m : Hax_basic.Counter) :
+  (do let r ← Hax_basic.subtract c n; Hax_basic.subtract r m)
+    = (do let s ← Hax_basic.add n m; Hax_basic.subtract c s) := by
+  simp [Hax_basic.add_eq, Hax_basic.subtract_eq, Core.Num.Impl_8.wrapping_add, Core.Num.Impl_8.wrapping_sub]
+  grind
+"
+    )]
+    ensures(|result| result.0 == c.0.wrapping_sub(n.0))
+    pub fn subtract(c: Counter, n: Counter) -> Counter {
+        Counter(generic::subtract(c.0, n.0))
+    }
 }
 
-/// Resets the counter to zero.
-/// 
-/// # Arguments
-/// * `c` - The current counter value
-/// 
-/// # Returns
-/// Always returns 0.
-/// 
-/// # Properties
-/// - `reset(c) == new_counter()`
-/// - `reset(c) == 0`
-/// TODO #[hax::ensures(|result| result == 0)]
-pub fn reset(_c: Counter) -> Counter {
-    0
+contract! {
+    /// Computes how far a counter advanced between two readings.
+    /// 
+    /// # Arguments
+    /// * `earlier` - The first reading
+    /// * `later` - The second reading
+    /// 
+    /// # Returns
+    /// `later - earlier` modulo 2^32, which is the true advance as long as the
+    /// counter wrapped at most once between the readings.
+    /// 
+    /// # Properties
+    /// - `add(earlier, delta(earlier, later)) == later`
+    /// - `delta(c, c) == 0`
+    /// - `delta(c, increment(c)) == 1`, including across the wrap
+    #[hax::lean::before("@[simp, spec]")]
+    #[hax::lean::after(
+        "-- Specification of delta
+theorem Hax_basic.delta_spec (earlier later : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄ -- Precondition (always true here)
+  (Hax_basic.delta earlier later) -- The function call
+  ⦃ ⇓ result => ⌜ result._0 = later._0 - earlier._0 ⌝ ⦄  -- Postcondition
+  := by
+  mvcgen [Hax_basic.delta, Hax_basic.subtract_eq, Core.Num.Impl_8.wrapping_sub]
+
+-- Law: adding the delta to the earlier reading gives the later one
+theorem Hax_basic.add_delta (earlier later : Hax_basic.Counter) :
+  (do let d ← Hax_basic.delta earlier later; Hax_basic.add earlier d) = pure later := by
+  simp [Hax_basic.delta, Hax_basic.add_eq, Hax_basic.subtract_eq, Core.Num.Impl_8.wrapping_add,
+    Core.Num.Impl_8.wrapping_sub]
+"
+    )]
+    ensures(|result| add(earlier, result) == later)
+    pub fn delta(earlier: Counter, later: Counter) -> Counter {
+        subtract(later, earlier)
+    }
+}
+
+contract! {
+    /// Resets the counter to zero.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// 
+    /// # Returns
+    /// Always returns 0.
+    /// 
+    /// # Properties
+    /// - `reset(c) == new_counter()`
+    /// - `reset(c) == 0`
+    #[hax::lean::after(
+        "-- reset is the generic reset at u32
+@[simp] theorem Hax_basic.reset_eq (c : Hax_basic.Counter) :
+  Hax_basic.reset c = pure ⟨0⟩ := by
+  simp [Hax_basic.reset, Hax_basic.Generic.reset, Hax_basic.Generic.CounterValue.ZERO,
+    Hax_basic.Generic.Impl_2]
+
+-- Specification of reset
+theorem Hax_basic.reset_spec (c : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄ -- Precondition (always true here)
+  (Hax_basic.reset c) -- The function call
+  ⦃ ⇓ result => ⌜ result._0 = 0 ⌝ ⦄  -- Postcondition
+  := by
+  mvcgen [Hax_basic.reset_eq]
+
+-- Law: reset is new_counter regardless of the input
+theorem Hax_basic.reset_eq_new_counter (c : Hax_basic.Counter) :
+  Hax_basic.reset c = Hax_basic.new_counter Rust_primitives.Hax.Tuple0.mk := by
+  simp [Hax_basic.reset_eq, Hax_basic.new_counter_eq]
+"
+    )]
+    ensures(|result| result.0 == 0)
+    pub fn reset(c: Counter) -> Counter {
+        Counter(generic::reset(c.0))
+    }
+}
+
+contract! {
+    /// Increments a counter by one, failing instead of wrapping.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// 
+    /// # Returns
+    /// `Ok(c + 1)`, or `Err(CounterError::Overflow { c, n: 1 })` when `c == Counter::MAX`.
+    /// 
+    /// # Properties
+    /// - `checked_increment(c) == checked_add(c, 1)`
+    /// - `checked_increment(c) == Ok(increment(c))` (when no overflow occurs)
+    /// - `checked_increment(Counter::MAX)` is an `Overflow` error
+    #[hax::lean::after(
+        "-- Specification of checked_increment: the `Ok` branch is `c + 1` over ℕ
+theorem Hax_basic.checked_increment_spec (c : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.checked_increment c)
+  ⦃ ⇓ result => ⌜ match result with
+      | Core.Result.Result.Ok r => r._0.toNat = c._0.toNat + 1
+      | Core.Result.Result.Err _ => c._0.toNat = 4294967295 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.checked_increment, Hax_basic.checked_add]
+  all_goals (simp_all; try omega)
+"
+    )]
+    ensures(|result| match result {
+        Ok(r) => r.0 as u64 == c.0 as u64 + 1,
+        Err(_) => c.0 == u32::MAX,
+    })
+    pub fn checked_increment(c: Counter) -> Result<Counter, CounterError> {
+        checked_add(c, Counter(1))
+    }
+}
+
+contract! {
+    /// Decrements a counter by one, failing instead of wrapping.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// 
+    /// # Returns
+    /// `Ok(c - 1)`, or `Err(CounterError::Underflow { c, n: 1 })` when `c == 0`.
+    /// 
+    /// # Properties
+    /// - `checked_decrement(c) == checked_subtract(c, 1)`
+    /// - `checked_decrement(c) == Ok(decrement(c))` (when no underflow occurs)
+    /// - `checked_decrement(new_counter())` is an `Underflow` error
+    #[hax::lean::after(
+        "-- Specification of checked_decrement: the `Ok` branch is `c - 1` over ℕ
+theorem Hax_basic.checked_decrement_spec (c : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.checked_decrement c)
+  ⦃ ⇓ result => ⌜ match result with
+      | Core.Result.Result.Ok r => r._0.toNat + 1 = c._0.toNat
+      | Core.Result.Result.Err _ => c._0.toNat = 0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.checked_decrement, Hax_basic.checked_subtract]
+  all_goals (simp_all; try omega)
+"
+    )]
+    ensures(|result| match result {
+        Ok(r) => r.0 as u64 + 1 == c.0 as u64,
+        Err(_) => c.0 == 0,
+    })
+    pub fn checked_decrement(c: Counter) -> Result<Counter, CounterError> {
+        checked_subtract(c, Counter(1))
+    }
+}
+
+contract! {
+    /// Adds a value to the counter, failing instead of wrapping.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// * `n` - The value to add
+    /// 
+    /// # Returns
+    /// `Ok(c + n)`, or `Err(CounterError::Overflow { c, n })` when the sum exceeds
+    /// `Counter::MAX`.
+    /// 
+    /// # Properties
+    /// - `checked_add(c, 0) == Ok(c)`
+    /// - `checked_add(c, n) == Ok(add(c, n))` (when no overflow occurs)
+    #[hax::lean::after(
+        "-- Specification of checked_add: the `Ok` branch is the mathematical sum
+theorem Hax_basic.checked_add_spec (c n : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.checked_add c n)
+  ⦃ ⇓ result => ⌜ match result with
+      | Core.Result.Result.Ok r => r._0.toNat = c._0.toNat + n._0.toNat
+      | Core.Result.Result.Err _ => c._0.toNat + n._0.toNat > 4294967295 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.checked_add]
+  all_goals (simp_all; try omega)
+"
+    )]
+    ensures(|result| match result {
+        Ok(r) => r.0 as u64 == c.0 as u64 + n.0 as u64,
+        Err(_) => c.0 as u64 + n.0 as u64 > u32::MAX as u64,
+    })
+    pub fn checked_add(c: Counter, n: Counter) -> Result<Counter, CounterError> {
+        if c.0 <= u32::MAX - n.0 {
+            Ok(Counter(c.0 + n.0))
+        } else {
+            Err(CounterError::Overflow { c, n })
+        }
+    }
+}
+
+contract! {
+    /// Subtracts a value from the counter, failing instead of wrapping.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// * `n` - The value to subtract
+    /// 
+    /// # Returns
+    /// `Ok(c - n)`, or `Err(CounterError::Underflow { c, n })` when `n > c`.
+    /// 
+    /// # Properties
+    /// - `checked_subtract(c, 0) == Ok(c)`
+    /// - `checked_subtract(c, c) == Ok(0)`
+    /// - `checked_subtract(c, n) == Ok(subtract(c, n))` (when no underflow occurs)
+    #[hax::lean::after(
+        "-- Specification of checked_subtract: the `Ok` branch is the mathematical difference
+theorem Hax_basic.checked_subtract_spec (c n : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.checked_subtract c n)
+  ⦃ ⇓ result => ⌜ match result with
+      | Core.Result.Result.Ok r => r._0.toNat + n._0.toNat = c._0.toNat
+      | Core.Result.Result.Err _ => n._0.toNat > c._0.toNat ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.checked_subtract]
+  all_goals (simp_all; try omega)
+"
+    )]
+    ensures(|result| match result {
+        Ok(r) => r.0 as u64 + n.0 as u64 == c.0 as u64,
+        Err(_) => n.0 > c.0,
+    })
+    pub fn checked_subtract(c: Counter, n: Counter) -> Result<Counter, CounterError> {
+        if n.0 <= c.0 {
+            Ok(Counter(c.0 - n.0))
+        } else {
+            Err(CounterError::Underflow { c, n })
+        }
+    }
+}
+
+contract! {
+    /// Increments a counter by one, clamping at `Counter::MAX`.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// 
+    /// # Returns
+    /// `c + 1`, or `Counter::MAX` when `c` is already at the maximum.
+    /// 
+    /// # Properties
+    /// - `saturating_increment(c) == saturating_add(c, 1)`
+    /// - `saturating_increment(Counter::MAX) == Counter::MAX`
+    #[hax::lean::after(
+        "-- Specification of saturating_increment: `min(c + 1, MAX)`
+theorem Hax_basic.saturating_increment_spec (c : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.saturating_increment c)
+  ⦃ ⇓ result => ⌜ result._0.toNat = min (c._0.toNat + 1) 4294967295 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.saturating_increment, Hax_basic.saturating_add]
+  all_goals (simp_all; try omega)
+"
+    )]
+    ensures(|result| if c.0 == u32::MAX {
+        result.0 == u32::MAX
+    } else {
+        result.0 as u64 == c.0 as u64 + 1
+    })
+    pub fn saturating_increment(c: Counter) -> Counter {
+        saturating_add(c, Counter(1))
+    }
+}
+
+contract! {
+    /// Decrements a counter by one, clamping at zero.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// 
+    /// # Returns
+    /// `c - 1`, or `0` when `c` is already zero.
+    /// 
+    /// # Properties
+    /// - `saturating_decrement(c) == saturating_subtract(c, 1)`
+    /// - `saturating_decrement(new_counter()) == 0`
+    #[hax::lean::after(
+        "-- Specification of saturating_decrement: `max(c - 1, 0)`
+theorem Hax_basic.saturating_decrement_spec (c : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.saturating_decrement c)
+  ⦃ ⇓ result => ⌜ (result._0.toNat : Int) = max ((c._0.toNat : Int) - 1) 0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.saturating_decrement, Hax_basic.saturating_subtract]
+  all_goals (simp_all; try omega)
+"
+    )]
+    ensures(|result| if c.0 == 0 { result.0 == 0 } else { result.0 as u64 + 1 == c.0 as u64 })
+    pub fn saturating_decrement(c: Counter) -> Counter {
+        saturating_subtract(c, Counter(1))
+    }
+}
+
+contract! {
+    /// Adds a value to the counter, clamping at `Counter::MAX`.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// * `n` - The value to add
+    /// 
+    /// # Returns
+    /// `c + n`, or `Counter::MAX` when the sum would overflow.
+    /// 
+    /// # Properties
+    /// - `saturating_add(c, 0) == c`
+    /// - `saturating_add(c, n) == add(c, n)` (when no overflow occurs)
+    /// - `saturating_add(c, n) >= c`
+    #[hax::lean::after(
+        "-- Specification of saturating_add: `min(c + n, MAX)`
+theorem Hax_basic.saturating_add_spec (c n : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.saturating_add c n)
+  ⦃ ⇓ result => ⌜ result._0.toNat = min (c._0.toNat + n._0.toNat) 4294967295 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.saturating_add]
+  all_goals (simp_all; try omega)
+"
+    )]
+    ensures(|result| if c.0 as u64 + n.0 as u64 > u32::MAX as u64 {
+        result.0 == u32::MAX
+    } else {
+        result.0 as u64 == c.0 as u64 + n.0 as u64
+    })
+    pub fn saturating_add(c: Counter, n: Counter) -> Counter {
+        if c.0 <= u32::MAX - n.0 {
+            Counter(c.0 + n.0)
+        } else {
+            Counter::MAX
+        }
+    }
+}
+
+contract! {
+    /// Subtracts a value from the counter, clamping at zero.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// * `n` - The value to subtract
+    /// 
+    /// # Returns
+    /// `c - n`, or `0` when `n > c`.
+    /// 
+    /// # Properties
+    /// - `saturating_subtract(c, 0) == c`
+    /// - `saturating_subtract(c, n) == subtract(c, n)` (when no underflow occurs)
+    /// - `saturating_subtract(c, n) <= c`
+    #[hax::lean::after(
+        "-- Specification of saturating_subtract: `max(c - n, 0)`
+theorem Hax_basic.saturating_subtract_spec (c n : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.saturating_subtract c n)
+  ⦃ ⇓ result => ⌜ (result._0.toNat : Int) = max ((c._0.toNat : Int) - n._0.toNat) 0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.saturating_subtract]
+  all_goals (simp_all; try omega)
+"
+    )]
+    // The explicit branch extracts to a plain `if` that the Lean proof can case on.
+    #[allow(clippy::implicit_saturating_sub)]
+    ensures(|result| if n.0 > c.0 {
+        result.0 == 0
+    } else {
+        result.0 as u64 + n.0 as u64 == c.0 as u64
+    })
+    pub fn saturating_subtract(c: Counter, n: Counter) -> Counter {
+        if n.0 <= c.0 {
+            Counter(c.0 - n.0)
+        } else {
+            Counter(0)
+        }
+    }
+}
+
+contract! {
+    /// Adds a value to the counter, reporting whether it wrapped.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// * `n` - The value to add
+    /// 
+    /// # Returns
+    /// `(add(c, n), carry)`, where `carry` is `true` when `c + n` exceeded
+    /// `Counter::MAX` and the result wrapped past zero.
+    /// 
+    /// # Properties
+    /// - `overflowing_add(c, n).0 == add(c, n)`
+    /// - `overflowing_add(c, n).1 == checked_add(c, n).is_err()`
+    /// - the true sum is `overflowing_add(c, n).0 + 2^32` when the carry is set
+    #[hax::lean::after(
+        "-- Specification of overflowing_add: the carry restores the true sum
+theorem Hax_basic.overflowing_add_spec (c n : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.overflowing_add c n)
+  ⦃ ⇓ result => ⌜ result.1._0.toNat + (if result.2 then 4294967296 else 0)
+      = c._0.toNat + n._0.toNat ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.overflowing_add, Hax_basic.add_eq, Core.Num.Impl_8.wrapping_add]
+  all_goals (simp_all [UInt32.toNat_add]; try omega)
+"
+    )]
+    ensures(|result| result.0 == add(c, n)
+        && result.1 == (c.0 as u64 + n.0 as u64 > u32::MAX as u64))
+    pub fn overflowing_add(c: Counter, n: Counter) -> (Counter, bool) {
+        (add(c, n), c.0 > u32::MAX - n.0)
+    }
+}
+
+contract! {
+    /// Subtracts a value from the counter, reporting whether it wrapped.
+    /// 
+    /// # Arguments
+    /// * `c` - The current counter value
+    /// * `n` - The value to subtract
+    /// 
+    /// # Returns
+    /// `(subtract(c, n), borrow)`, where `borrow` is `true` when `n > c` and the
+    /// result wrapped past zero.
+    /// 
+    /// # Properties
+    /// - `overflowing_subtract(c, n).0 == subtract(c, n)`
+    /// - `overflowing_subtract(c, n).1 == checked_subtract(c, n).is_err()`
+    /// - `overflowing_subtract(c, n).0 + n == c + 2^32` when the borrow is set
+    #[hax::lean::after(
+        "-- Specification of overflowing_subtract: the borrow restores the true difference
+theorem Hax_basic.overflowing_subtract_spec (c n : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.overflowing_subtract c n)
+  ⦃ ⇓ result => ⌜ result.1._0.toNat + n._0.toNat
+      = c._0.toNat + (if result.2 then 4294967296 else 0) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.overflowing_subtract, Hax_basic.subtract_eq, Core.Num.Impl_8.wrapping_sub]
+  all_goals (simp_all [UInt32.toNat_sub]; try omega)
+"
+    )]
+    ensures(|result| result.0 == subtract(c, n) && result.1 == (n.0 > c.0))
+    pub fn overflowing_subtract(c: Counter, n: Counter) -> (Counter, bool) {
+        (subtract(c, n), n.0 > c.0)
+    }
+}
+
+contract! {
+    /// The larger of two counters, compared as plain values.
+    /// 
+    /// The CRDTs and clocks merge by taking this pointwise.
+    ensures(|result| result.0 >= a.0 && result.0 >= b.0 && (result == a || result == b))
+    pub(crate) fn max(a: Counter, b: Counter) -> Counter {
+        if a.0 >= b.0 {
+            a
+        } else {
+            b
+        }
+    }
+}
+
+impl From<u32> for Counter {
+    fn from(value: u32) -> Counter {
+        Counter(value)
+    }
+}
+
+impl From<Counter> for u32 {
+    fn from(c: Counter) -> u32 {
+        c.0
+    }
+}
+
+/// Wrapping addition, identical to [`add`].
+impl Add for Counter {
+    type Output = Counter;
+
+    fn add(self, n: Counter) -> Counter {
+        add(self, n)
+    }
+}
+
+/// Wrapping subtraction, identical to [`subtract`].
+impl Sub for Counter {
+    type Output = Counter;
+
+    fn sub(self, n: Counter) -> Counter {
+        subtract(self, n)
+    }
+}
+
+impl AddAssign for Counter {
+    fn add_assign(&mut self, n: Counter) {
+        *self = add(*self, n);
+    }
+}
+
+impl SubAssign for Counter {
+    fn sub_assign(&mut self, n: Counter) {
+        *self = subtract(*self, n);
+    }
+}
+
+#[hax::exclude]
+impl fmt::Display for Counter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[hax::exclude]
+impl FromStr for Counter {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Counter, ParseIntError> {
+        s.parse().map(Counter)
+    }
 }
 
 #[cfg(test)]
@@ -122,54 +814,281 @@ mod tests {
 
     #[test]
     fn test_new_counter() {
-        assert_eq!(new_counter(), 0);
+        assert_eq!(new_counter(), Counter(0));
     }
 
     #[test]
     fn test_increment() {
-        assert_eq!(increment(0), 1);
-        assert_eq!(increment(5), 6);
-        assert_eq!(increment(increment(0)), 2);
+        assert_eq!(increment(Counter(0)), Counter(1));
+        assert_eq!(increment(Counter(5)), Counter(6));
+        assert_eq!(increment(increment(Counter(0))), Counter(2));
     }
 
     #[test]
     fn test_decrement() {
-        assert_eq!(decrement(1), 0);
-        assert_eq!(decrement(5), 4);
+        assert_eq!(decrement(Counter(1)), Counter(0));
+        assert_eq!(decrement(Counter(5)), Counter(4));
     }
 
     #[test]
     fn test_increment_decrement_inverse() {
-        let c = 42;
+        let c = Counter(42);
         assert_eq!(decrement(increment(c)), c);
     }
 
     #[test]
     fn test_add() {
-        assert_eq!(add(0, 0), 0);
-        assert_eq!(add(5, 3), 8);
-        assert_eq!(add(0, 1), increment(0));
+        assert_eq!(add(Counter(0), Counter(0)), Counter(0));
+        assert_eq!(add(Counter(5), Counter(3)), Counter(8));
+        assert_eq!(add(Counter(0), Counter(1)), increment(Counter(0)));
     }
 
     #[test]
     fn test_subtract() {
-        assert_eq!(subtract(5, 3), 2);
-        assert_eq!(subtract(5, 1), decrement(5));
+        assert_eq!(subtract(Counter(5), Counter(3)), Counter(2));
+        assert_eq!(subtract(Counter(5), Counter(1)), decrement(Counter(5)));
+    }
+
+    #[test]
+    fn test_delta() {
+        assert_eq!(delta(Counter(5), Counter(8)), Counter(3));
+        assert_eq!(delta(Counter(8), Counter(8)), Counter(0));
+        assert_eq!(delta(Counter::MAX, Counter(2)), Counter(3));
+        let samples = [0, 1, 1000, u32::MAX - 1, u32::MAX].map(Counter);
+        for a in samples {
+            assert_eq!(delta(a, increment(a)), Counter(1));
+            for b in samples {
+                assert_eq!(add(a, delta(a, b)), b);
+            }
+        }
     }
 
     #[test]
     fn test_reset() {
-        assert_eq!(reset(0), 0);
-        assert_eq!(reset(100), 0);
-        assert_eq!(reset(42), new_counter());
+        assert_eq!(reset(Counter(0)), Counter(0));
+        assert_eq!(reset(Counter(100)), Counter(0));
+        assert_eq!(reset(Counter(42)), new_counter());
     }
 
     #[test]
     fn test_wrapping_behavior() {
         // Test overflow
-        assert_eq!(increment(u32::MAX), 0);
+        assert_eq!(increment(Counter::MAX), Counter(0));
         // Test underflow
-        assert_eq!(decrement(0), u32::MAX);
+        assert_eq!(decrement(Counter(0)), Counter::MAX);
+    }
+
+    #[test]
+    fn test_checked_increment() {
+        assert_eq!(checked_increment(Counter(0)), Ok(Counter(1)));
+        assert_eq!(checked_increment(Counter(41)), Ok(increment(Counter(41))));
+        assert_eq!(
+            checked_increment(Counter::MAX),
+            Err(CounterError::Overflow {
+                c: Counter::MAX,
+                n: Counter(1)
+            })
+        );
+    }
+
+    #[test]
+    fn test_checked_decrement() {
+        assert_eq!(checked_decrement(Counter(1)), Ok(Counter(0)));
+        assert_eq!(checked_decrement(Counter(42)), Ok(decrement(Counter(42))));
+        assert_eq!(
+            checked_decrement(new_counter()),
+            Err(CounterError::Underflow {
+                c: Counter(0),
+                n: Counter(1)
+            })
+        );
+    }
+
+    #[test]
+    fn test_checked_add() {
+        assert_eq!(checked_add(Counter(5), Counter(0)), Ok(Counter(5)));
+        assert_eq!(
+            checked_add(Counter(5), Counter(3)),
+            Ok(add(Counter(5), Counter(3)))
+        );
+        assert_eq!(
+            checked_add(Counter(u32::MAX - 3), Counter(3)),
+            Ok(Counter::MAX)
+        );
+        assert_eq!(
+            checked_add(Counter(u32::MAX - 3), Counter(4)),
+            Err(CounterError::Overflow {
+                c: Counter(u32::MAX - 3),
+                n: Counter(4)
+            })
+        );
+    }
+
+    #[test]
+    fn test_checked_subtract() {
+        assert_eq!(checked_subtract(Counter(5), Counter(0)), Ok(Counter(5)));
+        assert_eq!(checked_subtract(Counter(5), Counter(5)), Ok(Counter(0)));
+        assert_eq!(
+            checked_subtract(Counter(5), Counter(3)),
+            Ok(subtract(Counter(5), Counter(3)))
+        );
+        assert_eq!(
+            checked_subtract(Counter(3), Counter(5)),
+            Err(CounterError::Underflow {
+                c: Counter(3),
+                n: Counter(5)
+            })
+        );
+    }
+
+    #[test]
+    fn test_saturating_increment() {
+        assert_eq!(saturating_increment(Counter(0)), Counter(1));
+        assert_eq!(saturating_increment(Counter(41)), increment(Counter(41)));
+        assert_eq!(saturating_increment(Counter::MAX), Counter::MAX);
+    }
+
+    #[test]
+    fn test_saturating_decrement() {
+        assert_eq!(saturating_decrement(Counter(1)), Counter(0));
+        assert_eq!(saturating_decrement(Counter(42)), decrement(Counter(42)));
+        assert_eq!(saturating_decrement(new_counter()), Counter(0));
     }
-}
 
+    #[test]
+    fn test_saturating_add() {
+        assert_eq!(saturating_add(Counter(5), Counter(0)), Counter(5));
+        assert_eq!(
+            saturating_add(Counter(5), Counter(3)),
+            add(Counter(5), Counter(3))
+        );
+        assert_eq!(
+            saturating_add(Counter(u32::MAX - 3), Counter(3)),
+            Counter::MAX
+        );
+        assert_eq!(
+            saturating_add(Counter(u32::MAX - 3), Counter(4)),
+            Counter::MAX
+        );
+        assert_eq!(saturating_add(Counter::MAX, Counter::MAX), Counter::MAX);
+    }
+
+    #[test]
+    fn test_saturating_subtract() {
+        assert_eq!(saturating_subtract(Counter(5), Counter(0)), Counter(5));
+        assert_eq!(
+            saturating_subtract(Counter(5), Counter(3)),
+            subtract(Counter(5), Counter(3))
+        );
+        assert_eq!(saturating_subtract(Counter(3), Counter(5)), Counter(0));
+        assert_eq!(saturating_subtract(Counter(0), Counter::MAX), Counter(0));
+    }
+
+    #[test]
+    fn test_algebraic_laws() {
+        let samples = [0, 1, 2, 1000, u32::MAX / 2, u32::MAX - 1, u32::MAX].map(Counter);
+        for c in samples {
+            assert_eq!(increment(increment(c)), add(c, Counter(2)));
+            assert_eq!(decrement(increment(c)), c);
+            assert_eq!(increment(decrement(c)), c);
+            assert_eq!(add(c, Counter(0)), c);
+            assert_eq!(add(c, Counter(1)), increment(c));
+            assert_eq!(subtract(c, Counter(0)), c);
+            assert_eq!(subtract(c, Counter(1)), decrement(c));
+            assert_eq!(reset(c), new_counter());
+            for n in samples {
+                assert_eq!(add(c, n), add(n, c));
+                assert_eq!(subtract(add(c, n), n), c);
+                for m in samples {
+                    assert_eq!(add(add(c, n), m), add(c, add(n, m)));
+                    assert_eq!(subtract(subtract(c, n), m), subtract(c, add(n, m)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_is_repeated_increment() {
+        let mut n = Counter(u32::MAX - 5);
+        for _ in 0..10 {
+            n = increment(n);
+        }
+        assert_eq!(n, add(Counter(u32::MAX - 5), Counter(10)));
+    }
+
+    #[test]
+    fn test_overflowing_add() {
+        assert_eq!(overflowing_add(Counter(5), Counter(3)), (Counter(8), false));
+        assert_eq!(
+            overflowing_add(Counter(u32::MAX - 3), Counter(3)),
+            (Counter::MAX, false)
+        );
+        assert_eq!(
+            overflowing_add(Counter(u32::MAX - 3), Counter(4)),
+            (Counter(0), true)
+        );
+        assert_eq!(
+            overflowing_add(Counter::MAX, Counter::MAX),
+            (Counter(u32::MAX - 1), true)
+        );
+        let samples = [0, 1, 1000, u32::MAX - 1, u32::MAX];
+        for a in samples {
+            for b in samples {
+                let (r, carry) = overflowing_add(Counter(a), Counter(b));
+                assert_eq!((r.0, carry), a.overflowing_add(b));
+                assert_eq!(carry, checked_add(Counter(a), Counter(b)).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_overflowing_subtract() {
+        assert_eq!(
+            overflowing_subtract(Counter(5), Counter(3)),
+            (Counter(2), false)
+        );
+        assert_eq!(
+            overflowing_subtract(Counter(5), Counter(5)),
+            (Counter(0), false)
+        );
+        assert_eq!(
+            overflowing_subtract(Counter(3), Counter(5)),
+            (Counter(u32::MAX - 1), true)
+        );
+        let samples = [0, 1, 1000, u32::MAX - 1, u32::MAX];
+        for a in samples {
+            for b in samples {
+                let (r, borrow) = overflowing_subtract(Counter(a), Counter(b));
+                assert_eq!((r.0, borrow), a.overflowing_sub(b));
+                assert_eq!(borrow, checked_subtract(Counter(a), Counter(b)).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_operators_match_free_functions() {
+        let samples = [0, 1, 1000, u32::MAX - 1, u32::MAX].map(Counter);
+        for a in samples {
+            for b in samples {
+                assert_eq!(a + b, add(a, b));
+                assert_eq!(a - b, subtract(a, b));
+                let mut x = a;
+                x += b;
+                assert_eq!(x, add(a, b));
+                x -= b;
+                assert_eq!(x, a);
+            }
+        }
+        assert_eq!(Counter::MAX + Counter(1), new_counter());
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Counter::from(7), Counter(7));
+        assert_eq!(u32::from(Counter(7)), 7);
+        assert_eq!(Counter(4294967295).to_string(), "4294967295");
+        assert_eq!("42".parse::<Counter>(), Ok(Counter(42)));
+        assert!("-1".parse::<Counter>().is_err());
+        assert!("4294967296".parse::<Counter>().is_err());
+    }
+}
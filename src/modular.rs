@@ -0,0 +1,328 @@
+//! A counter that wraps at an arbitrary modulus.
+//!
+//! [`ModCounter<M>`] cycles through `0, 1, ..., M - 1` and back to `0`, as
+//! needed for ring buffer indices, round-robin selection or day-of-week
+//! cycling. Every operation is computed with the functions in the crate root
+//! on a value kept below `M`, so the `u32` operations never actually wrap.
+
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
+use crate::contracts::contract;
+use crate::Counter;
+use hax_lib as hax;
+
+/// A counter value in `[0, M)`.
+///
+/// The field is private so the invariant `value < M` can only be
+/// established through [`ModCounter::new`] or [`ModCounter::from_counter`].
+/// `M` must be non-zero; `ModCounter<0>` fails to compile when used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModCounter<const M: u32> {
+    value: Counter,
+}
+
+impl<const M: u32> ModCounter<M> {
+    const NON_ZERO_MODULUS: () = assert!(M > 0, "ModCounter modulus must be non-zero");
+
+    contract! {
+        /// Creates a counter at zero.
+        ensures(|result| result.invariant())
+        pub fn new() -> ModCounter<M> {
+            let () = Self::NON_ZERO_MODULUS;
+            ModCounter {
+                value: crate::new_counter(),
+            }
+        }
+    }
+
+    contract! {
+        /// Creates a counter holding `c mod M`.
+        ensures(|result| result.invariant() && result.value.0 == c.0 % M)
+        pub fn from_counter(c: Counter) -> ModCounter<M> {
+            let () = Self::NON_ZERO_MODULUS;
+            ModCounter {
+                value: Counter(c.0 % M),
+            }
+        }
+    }
+
+    /// The current value, always below `M`.
+    pub fn value(self) -> Counter {
+        self.value
+    }
+
+    /// The invariant every `ModCounter` satisfies: `value < M`.
+    pub fn invariant(self) -> bool {
+        self.value.0 < M
+    }
+
+    contract! {
+        /// Increments the counter, wrapping from `M - 1` to `0`.
+        ///
+        /// # Properties
+        /// - `m.increment().decrement() == m`
+        /// - `m.increment() == m.add(1)`
+        #[hax::lean::after(
+            "-- increment stays in [0, M) and is undone by decrement, with no wrap exceptions
+theorem Hax_basic.Modular.Impl.increment_spec (M : u32) (m : Hax_basic.Modular.ModCounter M)
+  (h : m.value._0 < M) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Modular.Impl.increment M m)
+  ⦃ ⇓ r => ⌜ r.value._0 < M ∧ r.value._0.toNat = (m.value._0.toNat + 1) % M.toNat ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Modular.Impl.increment, Hax_basic.increment_eq, Core.Num.Impl_8.wrapping_add]
+  all_goals (simp_all [Nat.mod_eq_of_lt]; try omega)
+
+theorem Hax_basic.Modular.Impl.increment_decrement_inverse (M : u32)
+  (m : Hax_basic.Modular.ModCounter M) (h : m.value._0 < M) :
+  (do let r ← Hax_basic.Modular.Impl.increment M m; Hax_basic.Modular.Impl.decrement M r)
+    = pure m := by
+  simp [Hax_basic.Modular.Impl.increment, Hax_basic.Modular.Impl.decrement,
+    Hax_basic.increment_eq, Hax_basic.decrement_eq, Core.Num.Impl_8.wrapping_add,
+    Core.Num.Impl_8.wrapping_sub]
+  split <;> simp_all <;> grind
+"
+        )]
+        requires(self.invariant())
+        ensures(|result| result.invariant())
+        pub fn increment(self) -> ModCounter<M> {
+            if self.value.0 == M - 1 {
+                ModCounter {
+                    value: crate::new_counter(),
+                }
+            } else {
+                ModCounter {
+                    value: crate::increment(self.value),
+                }
+            }
+        }
+    }
+
+    contract! {
+        /// Decrements the counter, wrapping from `0` to `M - 1`.
+        ///
+        /// # Properties
+        /// - `m.decrement().increment() == m`
+        /// - `m.decrement() == m.subtract(1)`
+        #[hax::lean::after(
+            "-- decrement stays in [0, M) and is undone by increment, with no wrap exceptions
+theorem Hax_basic.Modular.Impl.decrement_spec (M : u32) (m : Hax_basic.Modular.ModCounter M)
+  (h : m.value._0 < M) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Modular.Impl.decrement M m)
+  ⦃ ⇓ r => ⌜ r.value._0 < M ∧ (r.value._0.toNat + 1) % M.toNat = m.value._0.toNat ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Modular.Impl.decrement, Hax_basic.decrement_eq, Core.Num.Impl_8.wrapping_sub]
+  all_goals (simp_all [Nat.mod_eq_of_lt]; try omega)
+
+theorem Hax_basic.Modular.Impl.decrement_increment_inverse (M : u32)
+  (m : Hax_basic.Modular.ModCounter M) (h : m.value._0 < M) :
+  (do let r ← Hax_basic.Modular.Impl.decrement M m; Hax_basic.Modular.Impl.increment M r)
+    = pure m := by
+  simp [Hax_basic.Modular.Impl.increment, Hax_basic.Modular.Impl.decrement,
+    Hax_basic.increment_eq, Hax_basic.decrement_eq, Core.Num.Impl_8.wrapping_add,
+    Core.Num.Impl_8.wrapping_sub]
+  split <;> simp_all <;> grind
+"
+        )]
+        requires(self.invariant())
+        ensures(|result| result.invariant())
+        pub fn decrement(self) -> ModCounter<M> {
+            if self.value.0 == 0 {
+                ModCounter {
+                    value: Counter(M - 1),
+                }
+            } else {
+                ModCounter {
+                    value: crate::decrement(self.value),
+                }
+            }
+        }
+    }
+
+    contract! {
+        /// Adds `n`, wrapping at `M`.
+        ///
+        /// # Returns
+        /// `(value + n) mod M`, computed without leaving `[0, M)`.
+        // The `requires` on the invariant lives here, where `add_spec` takes it
+        // as a hypothesis; `Add<Counter>` below forwards to this method.
+        #[allow(clippy::should_implement_trait)]
+        #[hax::lean::after(
+            "-- add stays in [0, M) and is addition modulo M
+theorem Hax_basic.Modular.Impl.add_spec (M : u32) (m : Hax_basic.Modular.ModCounter M)
+  (n : Hax_basic.Counter) (h : m.value._0 < M) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Modular.Impl.add M m n)
+  ⦃ ⇓ r => ⌜ r.value._0 < M
+      ∧ r.value._0.toNat = (m.value._0.toNat + n._0.toNat) % M.toNat ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Modular.Impl.add, Hax_basic.add_eq, Hax_basic.subtract_eq,
+    Core.Num.Impl_8.wrapping_add, Core.Num.Impl_8.wrapping_sub]
+  all_goals (simp_all [Nat.add_mod, Nat.mod_lt]; try omega)
+"
+        )]
+        requires(self.invariant())
+        ensures(|result| result.invariant())
+        pub fn add(self, n: Counter) -> ModCounter<M> {
+            let r = n.0 % M;
+            // `value + r` stays below `2 * M`; fold the overflow back without wrapping a `u32`.
+            if self.value.0 >= M - r {
+                ModCounter {
+                    value: crate::subtract(self.value, Counter(M - r)),
+                }
+            } else {
+                ModCounter {
+                    value: crate::add(self.value, Counter(r)),
+                }
+            }
+        }
+    }
+
+    contract! {
+        /// Subtracts `n`, wrapping at `M`.
+        ///
+        /// # Returns
+        /// `(value - n) mod M`, computed without leaving `[0, M)`.
+        #[hax::lean::after(
+            "-- subtract stays in [0, M) and undoes add
+theorem Hax_basic.Modular.Impl.subtract_spec (M : u32) (m : Hax_basic.Modular.ModCounter M)
+  (n : Hax_basic.Counter) (h : m.value._0 < M) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Modular.Impl.subtract M m n)
+  ⦃ ⇓ r => ⌜ r.value._0 < M
+      ∧ (r.value._0.toNat + n._0.toNat) % M.toNat = m.value._0.toNat ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Modular.Impl.subtract, Hax_basic.add_eq, Hax_basic.subtract_eq,
+    Core.Num.Impl_8.wrapping_add, Core.Num.Impl_8.wrapping_sub]
+  all_goals (simp_all [Nat.add_mod, Nat.mod_lt]; try omega)
+"
+        )]
+        requires(self.invariant())
+        ensures(|result| result.invariant())
+        pub fn subtract(self, n: Counter) -> ModCounter<M> {
+            let r = n.0 % M;
+            if self.value.0 >= r {
+                ModCounter {
+                    value: crate::subtract(self.value, Counter(r)),
+                }
+            } else {
+                ModCounter {
+                    value: crate::add(self.value, Counter(M - r)),
+                }
+            }
+        }
+    }
+
+    contract! {
+        /// Resets the counter to zero.
+        ensures(|result| result.invariant() && result.value == crate::new_counter())
+        pub fn reset(self) -> ModCounter<M> {
+            ModCounter::new()
+        }
+    }
+}
+
+impl<const M: u32> Default for ModCounter<M> {
+    fn default() -> ModCounter<M> {
+        ModCounter::new()
+    }
+}
+
+/// Adds a `Counter` modulo `M`, identical to [`ModCounter::add`].
+impl<const M: u32> Add<Counter> for ModCounter<M> {
+    type Output = ModCounter<M>;
+
+    fn add(self, n: Counter) -> ModCounter<M> {
+        ModCounter::add(self, n)
+    }
+}
+
+/// Subtracts a `Counter` modulo `M`, identical to [`ModCounter::subtract`].
+impl<const M: u32> Sub<Counter> for ModCounter<M> {
+    type Output = ModCounter<M>;
+
+    fn sub(self, n: Counter) -> ModCounter<M> {
+        ModCounter::subtract(self, n)
+    }
+}
+
+impl<const M: u32> AddAssign<Counter> for ModCounter<M> {
+    fn add_assign(&mut self, n: Counter) {
+        *self = ModCounter::add(*self, n);
+    }
+}
+
+impl<const M: u32> SubAssign<Counter> for ModCounter<M> {
+    fn sub_assign(&mut self, n: Counter) {
+        *self = ModCounter::subtract(*self, n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: [u32; 7] = [0, 1, 2, 6, 7, u32::MAX - 1, u32::MAX];
+
+    fn check_against_u64<const M: u32>() {
+        for v in SAMPLES {
+            let m = ModCounter::<M>::from_counter(Counter(v));
+            let v = v as u64 % M as u64;
+            assert_eq!(m.value().0 as u64, v);
+            assert_eq!(m.increment().value().0 as u64, (v + 1) % M as u64);
+            assert_eq!(m.increment().decrement(), m);
+            assert_eq!(m.decrement().increment(), m);
+            for n in SAMPLES {
+                let sum = m.add(Counter(n));
+                let diff = m.subtract(Counter(n));
+                assert!(sum.invariant() && diff.invariant());
+                assert_eq!(sum.value().0 as u64, (v + n as u64) % M as u64);
+                assert_eq!((diff.value().0 as u64 + n as u64) % M as u64, v);
+                assert_eq!(sum.subtract(Counter(n)), m);
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_u64_arithmetic() {
+        check_against_u64::<1>();
+        check_against_u64::<7>();
+        check_against_u64::<1000>();
+        check_against_u64::<{ u32::MAX }>();
+    }
+
+    #[test]
+    fn test_day_of_week_cycles() {
+        let sunday = ModCounter::<7>::from_counter(Counter(6));
+        assert_eq!(sunday.increment(), ModCounter::new());
+        assert_eq!(ModCounter::<7>::new().decrement(), sunday);
+        assert_eq!(sunday.add(Counter(7 * 52 + 1)).value(), Counter(0));
+        assert_eq!(sunday.reset(), ModCounter::default());
+    }
+
+    #[test]
+    fn test_operators_match_methods() {
+        let m = ModCounter::<7>::from_counter(Counter(5));
+        assert_eq!(m + Counter(4), m.add(Counter(4)));
+        assert_eq!(m - Counter(9), m.subtract(Counter(9)));
+        let mut i = m;
+        i += Counter(4);
+        assert_eq!(i.value(), Counter(2));
+        i -= Counter(4);
+        assert_eq!(i, m);
+    }
+
+    #[test]
+    fn test_ring_buffer_index() {
+        let mut i = ModCounter::<4>::new();
+        let visited: Vec<u32> = (0..10)
+            .map(|_| {
+                let v = i.value().0;
+                i = i.increment();
+                v
+            })
+            .collect();
+        assert_eq!(visited, [0, 1, 2, 3, 0, 1, 2, 3, 0, 1]);
+    }
+}
@@ -0,0 +1,184 @@
+//! A counter that can only move forward.
+//!
+//! [`MonotonicCounter`] exposes `increment` and `add` and nothing that could
+//! lower its value: there is no `decrement`, `subtract` or `reset`, and the
+//! value field is private. Instead of wrapping at `Counter::MAX` it reports
+//! exhaustion, so every value it returns is strictly greater than the one it
+//! was computed from. This is the property audit trails and nonces rely on.
+
+use crate::contracts::contract;
+use crate::Counter;
+use hax_lib as hax;
+
+/// Why a monotonic counter refused to advance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonotonicError {
+    /// `c + n` would exceed `Counter::MAX`; the counter is exhausted rather
+    /// than wrapping back to a smaller value.
+    Exhausted { c: Counter, n: Counter },
+    /// Adding zero would not advance the counter.
+    ZeroStep,
+}
+
+/// A counter value that never decreases.
+///
+/// Only forward operations exist:
+///
+/// ```compile_fail
+/// use hax_basic::monotonic::MonotonicCounter;
+///
+/// let _ = MonotonicCounter::new().decrement();
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MonotonicCounter {
+    value: Counter,
+}
+
+impl MonotonicCounter {
+    /// Creates a counter at `new_counter()`.
+    pub fn new() -> MonotonicCounter {
+        MonotonicCounter::starting_at(crate::new_counter())
+    }
+
+    /// Creates a counter that continues from `value`, for example one
+    /// restored from storage.
+    pub fn starting_at(value: Counter) -> MonotonicCounter {
+        MonotonicCounter { value }
+    }
+
+    /// The current value.
+    pub fn value(self) -> Counter {
+        self.value
+    }
+
+    contract! {
+        /// Advances the counter by one.
+        ///
+        /// # Returns
+        /// `increment(value)`, or `Err(Exhausted)` at `Counter::MAX`.
+        ///
+        /// # Properties
+        /// - `m.increment()?.value() > m.value()`
+        #[hax::lean::after(
+            "-- Every value returned by increment is strictly greater than the input
+theorem Hax_basic.Monotonic.Impl.increment_strictly_increases
+  (m : Hax_basic.Monotonic.MonotonicCounter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Monotonic.Impl.increment m)
+  ⦃ ⇓ result => ⌜ ∀ r, result = Core.Result.Result.Ok r → m.value._0 < r.value._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Monotonic.Impl.increment, Hax_basic.increment_eq, Core.Num.Impl_8.wrapping_add]
+  all_goals (simp_all; try omega)
+"
+        )]
+        ensures(|result| match result {
+            Ok(m) => m.value.0 > self.value.0 && m.value == crate::increment(self.value),
+            Err(_) => self.value == Counter::MAX,
+        })
+        pub fn increment(self) -> Result<MonotonicCounter, MonotonicError> {
+            if self.value == Counter::MAX {
+                Err(MonotonicError::Exhausted {
+                    c: self.value,
+                    n: Counter(1),
+                })
+            } else {
+                Ok(MonotonicCounter {
+                    value: crate::increment(self.value),
+                })
+            }
+        }
+    }
+
+    contract! {
+        /// Advances the counter by `n`.
+        ///
+        /// # Returns
+        /// `add(value, n)`, `Err(ZeroStep)` when `n` is zero, or `Err(Exhausted)`
+        /// when the sum would exceed `Counter::MAX`.
+        ///
+        /// # Properties
+        /// - `m.add(n)?.value() > m.value()`
+        /// - `m.add(1) == m.increment()`
+        // Reports exhaustion through a `Result` instead of wrapping, so this
+        // cannot be `Add::add` without giving up the strict increase.
+        #[allow(clippy::should_implement_trait)]
+        #[hax::lean::after(
+            "-- Every value returned by add is strictly greater than the input
+theorem Hax_basic.Monotonic.Impl.add_strictly_increases
+  (m : Hax_basic.Monotonic.MonotonicCounter) (n : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Monotonic.Impl.add m n)
+  ⦃ ⇓ result => ⌜ ∀ r, result = Core.Result.Result.Ok r → m.value._0 < r.value._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Monotonic.Impl.add, Hax_basic.add_eq, Core.Num.Impl_8.wrapping_add]
+  all_goals (simp_all [UInt32.toNat_add]; try omega)
+"
+        )]
+        ensures(|result| match result {
+            Ok(m) => m.value.0 > self.value.0 && m.value == crate::add(self.value, n),
+            Err(_) => n.0 == 0 || self.value.0 as u64 + n.0 as u64 > u32::MAX as u64,
+        })
+        pub fn add(self, n: Counter) -> Result<MonotonicCounter, MonotonicError> {
+            if n.0 == 0 {
+                Err(MonotonicError::ZeroStep)
+            } else if self.value.0 > u32::MAX - n.0 {
+                Err(MonotonicError::Exhausted { c: self.value, n })
+            } else {
+                Ok(MonotonicCounter {
+                    value: crate::add(self.value, n),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strictly_increasing() {
+        let mut m = MonotonicCounter::new();
+        let mut previous = m.value();
+        for n in [1, 1, 7, 1000, 1 << 20] {
+            m = m.add(Counter(n)).unwrap();
+            assert!(m.value() > previous);
+            m = m.increment().unwrap();
+            assert!(m.value() > previous);
+            previous = m.value();
+        }
+        assert_eq!(
+            MonotonicCounter::starting_at(Counter(41)).add(Counter(1)),
+            MonotonicCounter::starting_at(Counter(41)).increment()
+        );
+    }
+
+    #[test]
+    fn test_exhaustion_instead_of_wrapping() {
+        let last = MonotonicCounter::starting_at(Counter::MAX);
+        assert_eq!(
+            last.increment(),
+            Err(MonotonicError::Exhausted {
+                c: Counter::MAX,
+                n: Counter(1)
+            })
+        );
+        let near = MonotonicCounter::starting_at(Counter(u32::MAX - 2));
+        assert_eq!(near.add(Counter(2)).unwrap().value(), Counter::MAX);
+        assert_eq!(
+            near.add(Counter(3)),
+            Err(MonotonicError::Exhausted {
+                c: Counter(u32::MAX - 2),
+                n: Counter(3)
+            })
+        );
+    }
+
+    #[test]
+    fn test_zero_step_is_rejected() {
+        assert_eq!(
+            MonotonicCounter::new().add(Counter(0)),
+            Err(MonotonicError::ZeroStep)
+        );
+    }
+}
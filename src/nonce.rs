@@ -0,0 +1,321 @@
+//! Nonce generation for AEAD ciphers.
+//!
+//! A [`NonceCounter<N>`] issues `N`-byte nonces made of a fixed prefix and a
+//! big-endian `u32` counter in the last four bytes. The counter is a
+//! [`MonotonicCounter`], so it never goes back, and once the nonce with
+//! counter `Counter::MAX` has been issued every further request fails with
+//! [`NonceError::Exhausted`] instead of wrapping to a nonce already used.
+//!
+//! The generator is deliberately neither `Clone` nor `Copy`: a copy of its
+//! state would issue the same nonces again.
+
+use crate::contracts::contract;
+use crate::monotonic::MonotonicCounter;
+use crate::Counter;
+use hax_lib as hax;
+
+/// A 96-bit nonce generator, the nonce size of AES-GCM and ChaCha20-Poly1305.
+pub type Nonce96Counter = NonceCounter<12>;
+
+/// Why no nonce was issued.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceError {
+    /// All `2^32` nonces for this prefix have been issued.
+    Exhausted,
+}
+
+/// Issues unique `N`-byte nonces: `N - 4` prefix bytes then a big-endian counter.
+///
+/// `N` must be at least 4; smaller sizes fail to compile when used.
+#[derive(Debug, PartialEq, Eq)]
+pub struct NonceCounter<const N: usize> {
+    prefix: [u8; N],
+    next: MonotonicCounter,
+    exhausted: bool,
+}
+
+impl<const N: usize> NonceCounter<N> {
+    const HAS_COUNTER_BYTES: () = assert!(N >= 4, "nonces need at least 4 counter bytes");
+
+    /// Creates a generator with an all-zero prefix.
+    pub fn new() -> NonceCounter<N> {
+        let () = Self::HAS_COUNTER_BYTES;
+        NonceCounter {
+            prefix: [0; N],
+            next: MonotonicCounter::new(),
+            exhausted: false,
+        }
+    }
+
+    /// Creates a generator whose nonces start with `prefix`.
+    ///
+    /// # Returns
+    /// `None` unless `prefix` is exactly `N - 4` bytes long.
+    pub fn with_prefix(prefix: &[u8]) -> Option<NonceCounter<N>> {
+        NonceCounter::restore(prefix, 0)
+    }
+
+    /// Recreates a generator from its prefix and a persisted [`issued`] count.
+    ///
+    /// # Returns
+    /// `None` unless `prefix` is `N - 4` bytes long and `issued <= 2^32`.
+    ///
+    /// [`issued`]: NonceCounter::issued
+    pub fn restore(prefix: &[u8], issued: u64) -> Option<NonceCounter<N>> {
+        let () = Self::HAS_COUNTER_BYTES;
+        if prefix.len() != N - 4 || issued > 1 << 32 {
+            return None;
+        }
+        let mut bytes = [0; N];
+        bytes[..N - 4].copy_from_slice(prefix);
+        let exhausted = issued == 1 << 32;
+        // A generator that ran out stays at the counter of its last nonce.
+        let next = if exhausted {
+            Counter::MAX
+        } else {
+            Counter(issued as u32)
+        };
+        Some(NonceCounter {
+            prefix: bytes,
+            next: MonotonicCounter::starting_at(next),
+            exhausted,
+        })
+    }
+
+    /// How many nonces have been issued, between `0` and `2^32`.
+    ///
+    /// This is the state to persist. Store it durably before using the last
+    /// nonce it covers, so a restart can never issue that nonce again.
+    pub fn issued(&self) -> u64 {
+        if self.exhausted {
+            1 << 32
+        } else {
+            self.next.value().0 as u64
+        }
+    }
+
+    /// How many nonces can still be issued.
+    pub fn remaining(&self) -> u64 {
+        (1 << 32) - self.issued()
+    }
+
+    contract! {
+        /// The nonce for the current count and the generator that follows it.
+        #[hax::lean::after(
+            "-- The big-endian bytes of a counter
+def Hax_basic.Nonce.be_bytes (c : u32) : List u8 :=
+  [(c >>> 24).toUInt8, (c >>> 16).toUInt8, (c >>> 8).toUInt8, c.toUInt8]
+
+-- The big-endian encoding of the counter is injective
+theorem Hax_basic.Nonce.be_bytes_injective (a b : u32)
+  (h : Hax_basic.Nonce.be_bytes a = Hax_basic.Nonce.be_bytes b) : a = b := by
+  simp only [Hax_basic.Nonce.be_bytes, List.cons.injEq] at h
+  obtain ⟨h3, h2, h1, h0, -⟩ := h
+  bv_decide
+
+-- issue encodes the current count and then strictly advances it, or fails
+-- without changing anything
+theorem Hax_basic.Nonce.Impl.issue_spec (N : usize) (s : Hax_basic.Nonce.NonceCounter N) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Nonce.Impl.issue N s)
+  ⦃ ⇓ ⟨s', r⟩ => ⌜ (∀ nonce, r = Core.Result.Result.Ok nonce →
+        ¬ s.exhausted
+        ∧ nonce.toList.drop (N.toNat - 4) = Hax_basic.Nonce.be_bytes s.next.value._0
+        ∧ (s'.exhausted ∨ s.next.value._0 < s'.next.value._0))
+      ∧ (∀ e, r = Core.Result.Result.Err e → s' = s) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Nonce.Impl.issue, Hax_basic.Nonce.Impl.encode,
+    Hax_basic.Monotonic.Impl.increment_strictly_increases]
+  all_goals (simp_all [Hax_basic.Nonce.be_bytes]; try grind)
+"
+        )]
+        ensures(|result| match result.1 {
+            Ok(nonce) => {
+                !self.exhausted
+                    && nonce == self.encode(self.next.value())
+                    && result.0.prefix == self.prefix
+                    && result.0.issued() == self.issued() + 1
+            }
+            Err(_) => self.exhausted && result.0 == *self,
+        })
+        fn issue(&self) -> (NonceCounter<N>, Result<[u8; N], NonceError>) {
+            let same = NonceCounter {
+                prefix: self.prefix,
+                next: self.next,
+                exhausted: self.exhausted,
+            };
+            if self.exhausted {
+                return (same, Err(NonceError::Exhausted));
+            }
+            let nonce = self.encode(self.next.value());
+            let next = match self.next.increment() {
+                Ok(next) => NonceCounter { next, ..same },
+                Err(_) => NonceCounter {
+                    exhausted: true,
+                    ..same
+                },
+            };
+            (next, Ok(nonce))
+        }
+    }
+
+    /// Issues the next nonce.
+    ///
+    /// # Returns
+    /// The prefix followed by the big-endian counter, or
+    /// `Err(NonceError::Exhausted)` after the nonce for `Counter::MAX`.
+    ///
+    /// # Properties
+    /// - each success increases `issued()` by one
+    /// - over any number of calls, no two nonces returned are equal
+    #[hax::lean::after(
+        "-- next_nonce returns what issue computes and keeps the state it moves to
+theorem Hax_basic.Nonce.Impl.next_nonce_spec (N : usize) (s : Hax_basic.Nonce.NonceCounter N) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Nonce.Impl.next_nonce N s)
+  ⦃ ⇓ ⟨s', r⟩ => ⌜ (∀ nonce, r = Core.Result.Result.Ok nonce →
+        ¬ s.exhausted
+        ∧ nonce.toList.drop (N.toNat - 4) = Hax_basic.Nonce.be_bytes s.next.value._0
+        ∧ (s'.exhausted ∨ s.next.value._0 < s'.next.value._0))
+      ∧ (∀ e, r = Core.Result.Result.Err e → s' = s) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Nonce.Impl.next_nonce, Hax_basic.Nonce.Impl.issue_spec]
+  all_goals (simp_all; try grind)
+
+-- The nonces returned by k successive next_nonce calls from s, in order
+def Hax_basic.Nonce.trace (N : usize) :
+    Nat → Hax_basic.Nonce.NonceCounter N → RustM (List (RustArray u8 N))
+  | 0, _ => pure []
+  | k + 1, s => do
+    let ⟨s', r⟩ ← Hax_basic.Nonce.Impl.next_nonce N s
+    let rest ← Hax_basic.Nonce.trace N k s'
+    match r with
+    | Core.Result.Result.Ok nonce => pure (nonce :: rest)
+    | Core.Result.Result.Err _ => pure rest
+
+-- The counters behind a trace are strictly increasing and start at the
+-- generator's count, so a later call never reissues an earlier counter
+theorem Hax_basic.Nonce.trace_counters (N : usize) (k : Nat)
+  (s : Hax_basic.Nonce.NonceCounter N) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Nonce.trace N k s)
+  ⦃ ⇓ nonces => ⌜ ∃ cs : List u32,
+      nonces.map (·.toList.drop (N.toNat - 4)) = cs.map Hax_basic.Nonce.be_bytes
+      ∧ cs.Pairwise (· < ·)
+      ∧ ∀ c ∈ cs, ¬ s.exhausted ∧ s.next.value._0 ≤ c ⌝ ⦄
+  := by
+  induction k generalizing s with
+  | zero => mvcgen [Hax_basic.Nonce.trace]; exact ⟨[], by simp⟩
+  | succ k ih =>
+    mvcgen [Hax_basic.Nonce.trace, Hax_basic.Nonce.Impl.next_nonce_spec, ih]
+    all_goals (simp_all)
+    -- Ok: prepend the current counter, which is below every later one
+    · obtain ⟨cs, hmap, hsorted, hbound⟩ := ‹∃ cs, _›
+      refine ⟨s.next.value._0 :: cs, by simp_all, ?_, ?_⟩
+      · refine List.pairwise_cons.2 ⟨fun c hc => ?_, hsorted⟩
+        have := hbound c hc
+        grind
+      · intro c hc; simp at hc; rcases hc with rfl | hc <;> grind
+    -- Err: the state did not move, so the rest of the trace is unchanged
+    all_goals grind
+
+-- Over any number of successive calls, the returned nonces are pairwise distinct
+theorem Hax_basic.Nonce.issued_nonces_distinct (N : usize) (k : Nat)
+  (s : Hax_basic.Nonce.NonceCounter N) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Nonce.trace N k s)
+  ⦃ ⇓ nonces => ⌜ nonces.Nodup ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Nonce.trace_counters]
+  obtain ⟨cs, hmap, hsorted, _⟩ := ‹∃ cs, _›
+  have hcs : (cs.map Hax_basic.Nonce.be_bytes).Nodup :=
+    (hsorted.imp (fun h => ne_of_lt h)).map _
+      (fun a b hab => Hax_basic.Nonce.be_bytes_injective a b hab)
+  rw [← hmap] at hcs
+  exact List.Nodup.of_map _ hcs
+"
+    )]
+    pub fn next_nonce(&mut self) -> Result<[u8; N], NonceError> {
+        let (next, nonce) = self.issue();
+        *self = next;
+        nonce
+    }
+
+    /// The nonce for counter value `c`: the prefix, then `c` big-endian.
+    fn encode(&self, c: Counter) -> [u8; N] {
+        let mut nonce = self.prefix;
+        nonce[N - 4] = (c.0 >> 24) as u8;
+        nonce[N - 3] = (c.0 >> 16) as u8;
+        nonce[N - 2] = (c.0 >> 8) as u8;
+        nonce[N - 1] = c.0 as u8;
+        nonce
+    }
+}
+
+impl<const N: usize> Default for NonceCounter<N> {
+    fn default() -> NonceCounter<N> {
+        NonceCounter::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const PREFIX: [u8; 8] = [0xde, 0xad, 0xbe, 0xef, 0, 1, 2, 3];
+
+    #[test]
+    fn test_nonce_layout() {
+        let mut n = Nonce96Counter::with_prefix(&PREFIX).unwrap();
+        assert_eq!(
+            n.next_nonce(),
+            Ok([0xde, 0xad, 0xbe, 0xef, 0, 1, 2, 3, 0, 0, 0, 0])
+        );
+        assert_eq!(
+            n.next_nonce(),
+            Ok([0xde, 0xad, 0xbe, 0xef, 0, 1, 2, 3, 0, 0, 0, 1])
+        );
+        let mut bare = NonceCounter::<4>::restore(&[], 0x0102_0304).unwrap();
+        assert_eq!(bare.next_nonce(), Ok([1, 2, 3, 4]));
+        assert!(Nonce96Counter::with_prefix(&PREFIX[..7]).is_none());
+    }
+
+    #[test]
+    fn test_nonces_are_unique() {
+        let mut n = Nonce96Counter::new();
+        let nonces: HashSet<_> = (0..10_000).map(|_| n.next_nonce().unwrap()).collect();
+        assert_eq!(nonces.len(), 10_000);
+        assert_eq!(n.issued(), 10_000);
+    }
+
+    #[test]
+    fn test_refuses_to_issue_after_max() {
+        let mut n = Nonce96Counter::restore(&PREFIX, u32::MAX as u64 - 1).unwrap();
+        assert_eq!(n.remaining(), 2);
+        let second_last = n.next_nonce().unwrap();
+        let last = n.next_nonce().unwrap();
+        assert_eq!(second_last[8..], [0xff, 0xff, 0xff, 0xfe]);
+        assert_eq!(last[8..], [0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(n.remaining(), 0);
+        assert_eq!(n.next_nonce(), Err(NonceError::Exhausted));
+        assert_eq!(n.next_nonce(), Err(NonceError::Exhausted));
+        assert_eq!(n.issued(), 1 << 32);
+        assert_eq!(n, Nonce96Counter::restore(&PREFIX, 1 << 32).unwrap());
+    }
+
+    #[test]
+    fn test_persist_and_restore() {
+        let mut n = Nonce96Counter::with_prefix(&PREFIX).unwrap();
+        let issued_before: Vec<_> = (0..5).map(|_| n.next_nonce().unwrap()).collect();
+        let mut restored = Nonce96Counter::restore(&PREFIX, n.issued()).unwrap();
+        assert_eq!(restored, n);
+        let next = restored.next_nonce().unwrap();
+        assert!(!issued_before.contains(&next));
+        assert_eq!(Ok(next), n.next_nonce());
+
+        let exhausted = Nonce96Counter::restore(&PREFIX, 1 << 32).unwrap();
+        assert_eq!(exhausted.remaining(), 0);
+        assert!(Nonce96Counter::restore(&PREFIX, (1 << 32) + 1).is_none());
+    }
+}
@@ -0,0 +1,205 @@
+//! Counter operations represented as data.
+//!
+//! A counter program is a slice of [`Op`]s. [`apply`] interprets one
+//! operation with the free functions in the crate root and [`run`] folds a
+//! program over a starting value, so stored programs can be replayed and
+//! audited against the verified core.
+
+use crate::Counter;
+use hax_lib as hax;
+
+/// A single counter operation, mirroring the free functions in the crate root.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    /// `increment(c)`
+    Increment,
+    /// `decrement(c)`
+    Decrement,
+    /// `add(c, n)`
+    Add(Counter),
+    /// `subtract(c, n)`
+    Subtract(Counter),
+    /// `reset(c)`
+    Reset,
+}
+
+/// Applies a single operation to a counter.
+///
+/// # Arguments
+/// * `c` - The current counter value
+/// * `op` - The operation to perform
+///
+/// # Returns
+/// The result of the free function named by `op`.
+///
+/// # Properties
+/// - `apply(c, Op::Add(1)) == apply(c, Op::Increment)`
+/// - `apply(c, Op::Reset) == new_counter()`
+#[hax::lean::after(
+    "-- Pure Lean model of a single counter operation on the inner value
+def Hax_basic.Op.denote : Hax_basic.Op.Op → u32 → u32
+  | .Increment, c => c + 1
+  | .Decrement, c => c - 1
+  | .Add n, c => c + n._0
+  | .Subtract n, c => c - n._0
+  | .Reset, _ => 0
+
+-- apply agrees with the model
+@[spec]
+theorem Hax_basic.Op.apply_spec (c : Hax_basic.Counter) (op : Hax_basic.Op.Op) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Op.apply c op)
+  ⦃ ⇓ result => ⌜ result._0 = Hax_basic.Op.denote op c._0 ⌝ ⦄
+  := by
+  cases op <;> mvcgen [Hax_basic.Op.apply, Hax_basic.Op.denote, Hax_basic.increment_eq,
+    Hax_basic.decrement_eq, Hax_basic.add_eq, Hax_basic.subtract_eq, Hax_basic.reset_eq,
+    Core.Num.Impl_8.wrapping_add, Core.Num.Impl_8.wrapping_sub]
+"
+)]
+pub fn apply(c: Counter, op: Op) -> Counter {
+    match op {
+        Op::Increment => crate::increment(c),
+        Op::Decrement => crate::decrement(c),
+        Op::Add(n) => crate::add(c, n),
+        Op::Subtract(n) => crate::subtract(c, n),
+        Op::Reset => crate::reset(c),
+    }
+}
+
+/// Runs a program of operations, left to right, starting from `c`.
+///
+/// # Arguments
+/// * `c` - The starting counter value
+/// * `ops` - The operations to apply in order
+///
+/// # Returns
+/// `apply(... apply(apply(c, ops[0]), ops[1]) ..., ops[n - 1])`.
+///
+/// # Properties
+/// - `run(c, &[]) == c`
+/// - `run(c, &[a, b]) == apply(apply(c, a), b)`
+/// - `run(c, ops) == c + Σadds − Σsubs` (mod 2^32) when `ops` contains no `Reset`
+#[hax::lean::after(
+    "-- run is the left fold of the model over the program
+theorem Hax_basic.Op.run_spec (c : Hax_basic.Counter) (ops : RustSlice Hax_basic.Op.Op) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Op.run c ops)
+  ⦃ ⇓ result => ⌜ result._0 = ops.toList.foldl (fun c op => Hax_basic.Op.denote op c) c._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Op.run]
+  case inv =>
+    exact ⇓ ⟨i, acc⟩ => ⌜ acc._0 = (ops.toList.take i).foldl (fun c op => Hax_basic.Op.denote op c) c._0 ⌝
+  all_goals (simp_all [List.take_succ, List.foldl_append]; try grind)
+
+-- Total added (increments count as 1) and subtracted by a program, mod 2^32
+def Hax_basic.Op.adds : List Hax_basic.Op.Op → u32
+  | [] => 0
+  | .Increment :: ops => 1 + Hax_basic.Op.adds ops
+  | .Add n :: ops => n._0 + Hax_basic.Op.adds ops
+  | _ :: ops => Hax_basic.Op.adds ops
+
+def Hax_basic.Op.subs : List Hax_basic.Op.Op → u32
+  | [] => 0
+  | .Decrement :: ops => 1 + Hax_basic.Op.subs ops
+  | .Subtract n :: ops => n._0 + Hax_basic.Op.subs ops
+  | _ :: ops => Hax_basic.Op.subs ops
+
+-- Without Reset, a program computes c + Σadds − Σsubs modulo 2^32
+theorem Hax_basic.Op.fold_no_reset (ops : List Hax_basic.Op.Op) (c : u32)
+  (h : ∀ op ∈ ops, op ≠ Hax_basic.Op.Op.Reset) :
+  ops.foldl (fun c op => Hax_basic.Op.denote op c) c
+    = c + Hax_basic.Op.adds ops - Hax_basic.Op.subs ops := by
+  induction ops generalizing c with
+  | nil => simp [Hax_basic.Op.adds, Hax_basic.Op.subs]
+  | cons op ops ih =>
+    cases op <;> simp_all [Hax_basic.Op.denote, Hax_basic.Op.adds, Hax_basic.Op.subs] <;> grind
+
+theorem Hax_basic.Op.run_no_reset (c : Hax_basic.Counter) (ops : RustSlice Hax_basic.Op.Op)
+  (h : ∀ op ∈ ops.toList, op ≠ Hax_basic.Op.Op.Reset) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Op.run c ops)
+  ⦃ ⇓ result => ⌜ result._0 = c._0 + Hax_basic.Op.adds ops.toList - Hax_basic.Op.subs ops.toList ⌝ ⦄
+  := by
+  have := Hax_basic.Op.run_spec c ops
+  rw [← Hax_basic.Op.fold_no_reset ops.toList c._0 h]
+  exact this
+"
+)]
+// `run_spec` carries its loop invariant through the `fold_range` this extracts to.
+#[allow(clippy::needless_range_loop)]
+pub fn run(c: Counter, ops: &[Op]) -> Counter {
+    let mut c = c;
+    for i in 0..ops.len() {
+        c = apply(c, ops[i]);
+    }
+    c
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROGRAM: [Op; 6] = [
+        Op::Increment,
+        Op::Add(Counter(10)),
+        Op::Subtract(Counter(3)),
+        Op::Decrement,
+        Op::Add(Counter::MAX),
+        Op::Increment,
+    ];
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(
+            apply(Counter(5), Op::Increment),
+            crate::increment(Counter(5))
+        );
+        assert_eq!(
+            apply(Counter(5), Op::Decrement),
+            crate::decrement(Counter(5))
+        );
+        assert_eq!(
+            apply(Counter(5), Op::Add(Counter(3))),
+            crate::add(Counter(5), Counter(3))
+        );
+        assert_eq!(
+            apply(Counter(5), Op::Subtract(Counter(7))),
+            crate::subtract(Counter(5), Counter(7))
+        );
+        assert_eq!(apply(Counter(5), Op::Reset), crate::new_counter());
+    }
+
+    #[test]
+    fn test_run_is_composition_of_apply() {
+        assert_eq!(run(Counter(7), &[]), Counter(7));
+        for split in 0..=PROGRAM.len() {
+            let (a, b) = PROGRAM.split_at(split);
+            assert_eq!(run(Counter(7), &PROGRAM), run(run(Counter(7), a), b));
+        }
+        let folded = PROGRAM.iter().fold(Counter(7), |c, &op| apply(c, op));
+        assert_eq!(run(Counter(7), &PROGRAM), folded);
+    }
+
+    #[test]
+    fn test_run_without_reset_is_net_sum() {
+        let adds = 1u32.wrapping_add(10).wrapping_add(u32::MAX).wrapping_add(1);
+        let subs = 3u32 + 1;
+        for start in [0, 42, u32::MAX] {
+            assert_eq!(
+                run(Counter(start), &PROGRAM),
+                Counter(start.wrapping_add(adds).wrapping_sub(subs))
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_with_reset() {
+        assert_eq!(
+            run(
+                Counter(100),
+                &[Op::Add(Counter(5)), Op::Reset, Op::Increment]
+            ),
+            Counter(1)
+        );
+    }
+}
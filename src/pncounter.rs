@@ -0,0 +1,322 @@
+//! A positive-negative counter CRDT.
+//!
+//! A [`PNCounter<R>`] supports decrements across replicas by keeping two
+//! [`GCounter`]s: increments go to `positive`, decrements to `negative`, and
+//! the value is their difference. Both halves only grow, so merging each half
+//! with [`GCounter::merge`] keeps the convergence laws proven for the
+//! grow-only counter. [`PNCounter::delta_since`] ships only what a peer has
+//! not seen yet.
+
+use crate::contracts::contract;
+use crate::gcounter::GCounter;
+use crate::{Counter, CounterError};
+use hax_lib as hax;
+
+/// A counter over `R` replicas that can go up and down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PNCounter<const R: usize> {
+    positive: GCounter<R>,
+    negative: GCounter<R>,
+}
+
+impl<const R: usize> PNCounter<R> {
+    /// Creates a counter whose value is zero.
+    pub fn new() -> PNCounter<R> {
+        PNCounter {
+            positive: GCounter::new(),
+            negative: GCounter::new(),
+        }
+    }
+
+    /// Creates a counter from its two halves.
+    pub fn from_halves(positive: GCounter<R>, negative: GCounter<R>) -> PNCounter<R> {
+        PNCounter { positive, negative }
+    }
+
+    /// The grow-only counter of increments.
+    pub fn positive(self) -> GCounter<R> {
+        self.positive
+    }
+
+    /// The grow-only counter of decrements.
+    pub fn negative(self) -> GCounter<R> {
+        self.negative
+    }
+
+    contract! {
+        /// Records an increment at `replica`.
+        ///
+        /// # Panics
+        /// If `replica >= R`.
+        requires(replica < R)
+        pub fn increment(self, replica: usize) -> Result<PNCounter<R>, CounterError> {
+            self.add(replica, Counter(1))
+        }
+    }
+
+    contract! {
+        /// Records a decrement at `replica`.
+        ///
+        /// # Panics
+        /// If `replica >= R`.
+        requires(replica < R)
+        pub fn decrement(self, replica: usize) -> Result<PNCounter<R>, CounterError> {
+            self.subtract(replica, Counter(1))
+        }
+    }
+
+    contract! {
+        /// Records adding `n` at `replica`.
+        ///
+        /// # Returns
+        /// The counter with `n` added to `replica`'s positive slot, or
+        /// `Err(CounterError::Overflow)` when that slot would exceed `Counter::MAX`.
+        ///
+        /// # Panics
+        /// If `replica >= R`.
+        requires(replica < R)
+        pub fn add(self, replica: usize, n: Counter) -> Result<PNCounter<R>, CounterError> {
+            Ok(PNCounter {
+                positive: self.positive.add(replica, n)?,
+                negative: self.negative,
+            })
+        }
+    }
+
+    contract! {
+        /// Records subtracting `n` at `replica`.
+        ///
+        /// # Returns
+        /// The counter with `n` added to `replica`'s negative slot, or
+        /// `Err(CounterError::Overflow)` when that slot would exceed `Counter::MAX`.
+        ///
+        /// # Panics
+        /// If `replica >= R`.
+        requires(replica < R)
+        pub fn subtract(self, replica: usize, n: Counter) -> Result<PNCounter<R>, CounterError> {
+            Ok(PNCounter {
+                positive: self.positive,
+                negative: self.negative.add(replica, n)?,
+            })
+        }
+    }
+
+    /// Merges the state of another replica, half by half.
+    ///
+    /// # Properties
+    /// - `a.merge(b) == b.merge(a)`
+    /// - `a.merge(b.merge(c)) == a.merge(b).merge(c)`
+    /// - `a.merge(a) == a`
+    #[hax::lean::after(
+        "-- Law: merge is commutative
+theorem Hax_basic.Pncounter.Impl.merge_comm (R : usize) (a b : Hax_basic.Pncounter.PNCounter R) :
+  Hax_basic.Pncounter.Impl.merge R a b = Hax_basic.Pncounter.Impl.merge R b a := by
+  simp [Hax_basic.Pncounter.Impl.merge, Hax_basic.Gcounter.Impl.merge_comm]
+
+-- Law: merge is associative
+theorem Hax_basic.Pncounter.Impl.merge_assoc (R : usize)
+  (a b c : Hax_basic.Pncounter.PNCounter R) :
+  (do let bc ← Hax_basic.Pncounter.Impl.merge R b c; Hax_basic.Pncounter.Impl.merge R a bc)
+    = (do let ab ← Hax_basic.Pncounter.Impl.merge R a b
+          Hax_basic.Pncounter.Impl.merge R ab c) := by
+  have hp := Hax_basic.Gcounter.Impl.merge_assoc R a.positive b.positive c.positive
+  have hn := Hax_basic.Gcounter.Impl.merge_assoc R a.negative b.negative c.negative
+  mvcgen [Hax_basic.Pncounter.Impl.merge, Hax_basic.Gcounter.Impl.merge_spec] at hp hn ⊢
+  all_goals (simp_all; try grind)
+
+-- Law: merge is idempotent
+theorem Hax_basic.Pncounter.Impl.merge_idem (R : usize) (a : Hax_basic.Pncounter.PNCounter R) :
+  Hax_basic.Pncounter.Impl.merge R a a = pure a := by
+  simp [Hax_basic.Pncounter.Impl.merge, Hax_basic.Gcounter.Impl.merge_idem]
+"
+    )]
+    pub fn merge(self, other: PNCounter<R>) -> PNCounter<R> {
+        PNCounter {
+            positive: self.positive.merge(other.positive),
+            negative: self.negative.merge(other.negative),
+        }
+    }
+
+    /// The part of `self` that `since` has not seen, half by half.
+    ///
+    /// A replica that tracks the last state it shipped to a peer sends
+    /// `state.delta_since(last_shipped)` instead of the full state.
+    ///
+    /// # Properties
+    /// - `r.merge(a.delta_since(s)) == r.merge(a)` whenever `s.le(r)`
+    #[hax::lean::after(
+        "-- Law: merging the delta into any replica that has seen `since` equals
+-- merging the full state
+theorem Hax_basic.Pncounter.Impl.merge_delta_since (R : usize)
+  (r a s : Hax_basic.Pncounter.PNCounter R)
+  (hp : ∀ i < R.toNat, s.positive.slots.toList[i]!._0 ≤ r.positive.slots.toList[i]!._0)
+  (hn : ∀ i < R.toNat, s.negative.slots.toList[i]!._0 ≤ r.negative.slots.toList[i]!._0) :
+  (do let d ← Hax_basic.Pncounter.Impl.delta_since R a s; Hax_basic.Pncounter.Impl.merge R r d)
+    = Hax_basic.Pncounter.Impl.merge R r a := by
+  have dp := Hax_basic.Gcounter.Impl.merge_delta_since R r.positive a.positive s.positive hp
+  have dn := Hax_basic.Gcounter.Impl.merge_delta_since R r.negative a.negative s.negative hn
+  mvcgen [Hax_basic.Pncounter.Impl.delta_since, Hax_basic.Pncounter.Impl.merge] at dp dn ⊢
+  all_goals (simp_all; try grind)
+"
+    )]
+    pub fn delta_since(self, since: PNCounter<R>) -> PNCounter<R> {
+        PNCounter {
+            positive: self.positive.delta_since(since.positive),
+            negative: self.negative.delta_since(since.negative),
+        }
+    }
+
+    /// Whether `other` has seen every update `self` has.
+    pub fn le(self, other: PNCounter<R>) -> bool {
+        self.positive.le(other.positive) && self.negative.le(other.negative)
+    }
+
+    /// The net count: increments minus decrements across all replicas.
+    ///
+    /// # Properties
+    /// - `a.increment(i)?.value() == a.value() + 1`
+    /// - `a.decrement(i)?.value() == a.value() - 1`
+    pub fn value(self) -> i64 {
+        self.positive.value() as i64 - self.negative.value() as i64
+    }
+
+    /// The net count as a `Counter`, wrapping like the crate-root functions.
+    ///
+    /// # Returns
+    /// The counter reached by applying every recorded increment and decrement
+    /// to `new_counter()` with `increment`/`decrement`, in any order.
+    pub fn to_counter(self) -> Counter {
+        crate::subtract(
+            Counter(self.positive.value() as u32),
+            Counter(self.negative.value() as u32),
+        )
+    }
+}
+
+impl<const R: usize> Default for PNCounter<R> {
+    fn default() -> PNCounter<R> {
+        PNCounter::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLICAS: usize = 4;
+
+    type Replica = PNCounter<REPLICAS>;
+
+    /// A xorshift generator, so every run replays the same interleaving.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    #[test]
+    fn test_increment_and_decrement() {
+        let a = Replica::new()
+            .increment(0)
+            .unwrap()
+            .decrement(1)
+            .unwrap()
+            .decrement(1)
+            .unwrap()
+            .add(3, Counter(5))
+            .unwrap()
+            .subtract(0, Counter(2))
+            .unwrap();
+        assert_eq!(a.value(), 2);
+        assert_eq!(a.positive().value(), 6);
+        assert_eq!(a.negative().value(), 4);
+        let b = Replica::new().decrement(2).unwrap();
+        assert_eq!(b.value(), -1);
+        assert_eq!(b.to_counter(), crate::decrement(crate::new_counter()));
+    }
+
+    #[test]
+    fn test_merge_laws() {
+        let mut rng = Rng(0x5eed);
+        let mut half =
+            || GCounter::from_slots([(); REPLICAS].map(|_| Counter(rng.below(8) as u32)));
+        let states: Vec<Replica> = (0..6)
+            .map(|_| PNCounter::from_halves(half(), half()))
+            .collect();
+        for &a in &states {
+            assert_eq!(a.merge(a), a);
+            for &b in &states {
+                assert_eq!(a.merge(b), b.merge(a));
+                assert!(a.le(a.merge(b)));
+                for &c in &states {
+                    assert_eq!(a.merge(b.merge(c)), a.merge(b).merge(c));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulated_replicas_converge() {
+        for seed in 1..=20 {
+            let mut rng = Rng(seed);
+            let mut replicas = [Replica::new(); REPLICAS];
+            // shipped[i][k]: the last state replica `i` sent to replica `k`.
+            let mut shipped = [[Replica::new(); REPLICAS]; REPLICAS];
+            let mut expected: i64 = 0;
+            let mut expected_counter = crate::new_counter();
+            for _ in 0..500 {
+                let i = rng.below(REPLICAS);
+                match rng.below(4) {
+                    0 => {
+                        replicas[i] = replicas[i].increment(i).unwrap();
+                        expected += 1;
+                        expected_counter = crate::increment(expected_counter);
+                    }
+                    1 => {
+                        replicas[i] = replicas[i].decrement(i).unwrap();
+                        expected -= 1;
+                        expected_counter = crate::decrement(expected_counter);
+                    }
+                    2 => {
+                        let k = rng.below(REPLICAS);
+                        replicas[k] = replicas[k].merge(replicas[i]);
+                    }
+                    _ => {
+                        let k = rng.below(REPLICAS);
+                        let delta = replicas[i].delta_since(shipped[i][k]);
+                        assert_eq!(replicas[k].merge(delta), replicas[k].merge(replicas[i]));
+                        replicas[k] = replicas[k].merge(delta);
+                        shipped[i][k] = replicas[i];
+                    }
+                }
+            }
+            // Full gossip in a random order brings every replica to the same state.
+            for _ in 0..2 {
+                for _ in 0..REPLICAS * REPLICAS {
+                    let (i, k) = (rng.below(REPLICAS), rng.below(REPLICAS));
+                    replicas[k] = replicas[k].merge(replicas[i]);
+                }
+                for i in 0..REPLICAS {
+                    replicas[0] = replicas[0].merge(replicas[i]);
+                }
+                for k in 1..REPLICAS {
+                    replicas[k] = replicas[k].merge(replicas[0]);
+                }
+            }
+            for r in replicas {
+                assert_eq!(r, replicas[0]);
+                assert_eq!(r.value(), expected);
+                assert_eq!(r.to_counter(), expected_counter);
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! Rates from periodic counter readings.
+//!
+//! A reading is a `(timestamp, Counter)` pair. Consecutive readings are
+//! differenced with [`crate::delta`], so a counter that wraps between two
+//! readings still contributes its true advance, provided it wraps at most
+//! once per interval. Timestamps are in whatever unit the caller samples in,
+//! and rates are per that unit.
+
+use crate::contracts::contract;
+use crate::Counter;
+use hax_lib as hax;
+
+contract! {
+    /// The total advance over a series of readings, in time order.
+    ///
+    /// # Returns
+    /// The sum of `delta` over each pair of consecutive readings, or `0` for
+    /// fewer than two readings. Widening to `u64` keeps the sum exact even when
+    /// the counter wraps many times over the whole series: each of the at most
+    /// `2^32` pairs the precondition allows adds less than `2^32`.
+    ///
+    /// # Properties
+    /// - `total_delta(&[a, b]) == delta(a.1, b.1)`
+    /// - `total_delta(&[a, b, c]) == total_delta(&[a, b]) + total_delta(&[b, c])`
+    requires(readings.len() as u64 <= (1 << 32) + 1)
+    pub fn total_delta(readings: &[(u64, Counter)]) -> u64 {
+        let mut total: u64 = 0;
+        for i in 1..readings.len() {
+            total += crate::delta(readings[i - 1].1, readings[i].1).0 as u64;
+        }
+        total
+    }
+}
+
+/// The average rate of advance over a series of readings, in time order.
+///
+/// # Returns
+/// `total_delta(readings)` divided by the time from the first to the last
+/// reading, or `None` when there are fewer than two readings, no time passed
+/// between the first and the last, or there are more than `2^32 + 1`
+/// readings, the most `total_delta` accepts.
+#[hax::exclude]
+pub fn rate(readings: &[(u64, Counter)]) -> Option<f64> {
+    let (first, last) = (readings.first()?, readings.last()?);
+    if last.0 <= first.0 || readings.len() as u64 > (1 << 32) + 1 {
+        return None;
+    }
+    Some(total_delta(readings) as f64 / (last.0 - first.0) as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_total_delta_across_wraps() {
+        let readings = [
+            (0, Counter(u32::MAX - 10)),
+            (10, Counter(5)),
+            (20, Counter(u32::MAX)),
+            (30, Counter(20)),
+        ];
+        assert_eq!(total_delta(&readings), 16 + (u32::MAX - 5) as u64 + 21);
+        assert_eq!(total_delta(&readings[..1]), 0);
+        assert_eq!(total_delta(&[]), 0);
+    }
+
+    #[test]
+    fn test_rate() {
+        let readings = [(100, Counter::MAX), (110, Counter(49)), (150, Counter(249))];
+        assert_eq!(rate(&readings), Some(5.0));
+        assert_eq!(rate(&readings[..2]), Some(5.0));
+        assert_eq!(rate(&readings[..1]), None);
+        assert_eq!(rate(&[(5, Counter(0)), (5, Counter(9))]), None);
+    }
+}
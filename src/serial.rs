@@ -0,0 +1,160 @@
+//! Serial number arithmetic (RFC 1982) for wrapping counters.
+//!
+//! Because [`crate::increment`] wraps at `Counter::MAX`, plain `<` stops
+//! ordering counters correctly after a wrap. RFC 1982 instead compares two
+//! readings by the shorter way around the circle: `a` is before `b` when `b`
+//! is less than `2^31` steps ahead of `a`. Readings exactly `2^31` apart have
+//! no defined order, and the functions here return `None` for them.
+
+use crate::contracts::contract;
+use crate::Counter;
+use hax_lib as hax;
+
+/// `2^(SERIAL_BITS - 1)`, the distance at which comparison is undefined.
+pub const HALF_RANGE: u32 = 1 << 31;
+
+contract! {
+    /// The signed number of steps from `a` forward to `b`.
+    ///
+    /// # Returns
+    /// `Some(d)` with `-2^31 < d < 2^31` and `add(a, d) == b` (modulo 2^32), or
+    /// `None` when `a` and `b` are exactly `2^31` apart.
+    ///
+    /// # Properties
+    /// - `serial_distance(c, c) == Some(0)`
+    /// - `serial_distance(c, increment(c)) == Some(1)`
+    /// - `serial_distance(a, b) == serial_distance(b, a).map(|d| -d)`
+    #[hax::lean::after(
+        "-- A defined distance always leads from a to b
+theorem Hax_basic.Serial.serial_distance_spec (a b : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Serial.serial_distance a b)
+  ⦃ ⇓ result => ⌜ ∀ d, result = Core.Option.Option.Some d → a._0 + d.toUInt32 = b._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Serial.serial_distance, Hax_basic.subtract_eq, Core.Num.Impl_8.wrapping_sub]
+  all_goals (simp_all; try grind)
+"
+    )]
+    ensures(|result| match result {
+        Some(d) => crate::add(a, Counter(d as u32)) == b,
+        None => crate::subtract(b, a).0 == HALF_RANGE,
+    })
+    pub fn serial_distance(a: Counter, b: Counter) -> Option<i32> {
+        let d = crate::subtract(b, a).0;
+        if d == HALF_RANGE {
+            None
+        } else {
+            Some(d as i32)
+        }
+    }
+}
+
+/// Whether `a` comes before `b` in serial number order.
+///
+/// # Returns
+/// `Some(true)` when `b` is between 1 and `2^31 - 1` steps ahead of `a`,
+/// `Some(false)` when it is not, and `None` when the comparison is undefined.
+///
+/// # Properties
+/// - `serial_lt(c, increment(c)) == Some(true)` for every `c`, including `Counter::MAX`
+/// - `serial_lt(c, c) == Some(false)`
+/// - `serial_lt(a, b) == Some(true)` implies `serial_lt(b, a) == Some(false)`
+#[hax::lean::after(
+    "-- A counter is always before its increment, even across the wrap
+theorem Hax_basic.Serial.serial_lt_increment (c : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (do
+    let i ← Hax_basic.increment c
+    Hax_basic.Serial.serial_lt c i)
+  ⦃ ⇓ result => ⌜ result = Core.Option.Option.Some true ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.increment_eq, Hax_basic.Serial.serial_lt, Hax_basic.Serial.serial_distance,
+    Hax_basic.subtract_eq, Core.Num.Impl_8.wrapping_add, Core.Num.Impl_8.wrapping_sub]
+  all_goals (simp_all; try grind)
+
+-- Serial order is irreflexive
+theorem Hax_basic.Serial.serial_lt_irrefl (c : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Serial.serial_lt c c)
+  ⦃ ⇓ result => ⌜ result = Core.Option.Option.Some false ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Serial.serial_lt, Hax_basic.Serial.serial_distance, Hax_basic.subtract_eq,
+    Core.Num.Impl_8.wrapping_sub]
+  all_goals (simp_all; try grind)
+"
+)]
+// The explicit match extracts to a plain `match` that the Lean proof can case on.
+#[allow(clippy::manual_map)]
+pub fn serial_lt(a: Counter, b: Counter) -> Option<bool> {
+    match serial_distance(a, b) {
+        Some(d) => Some(d > 0),
+        None => None,
+    }
+}
+
+/// Whether `a` comes after `b` in serial number order.
+///
+/// # Properties
+/// - `serial_gt(a, b) == serial_lt(b, a)`
+/// - `serial_gt(increment(c), c) == Some(true)` for every `c`
+pub fn serial_gt(a: Counter, b: Counter) -> Option<bool> {
+    serial_lt(b, a)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: [u32; 7] = [
+        0,
+        1,
+        1000,
+        HALF_RANGE - 1,
+        HALF_RANGE,
+        u32::MAX - 1,
+        u32::MAX,
+    ];
+
+    #[test]
+    fn test_lt_across_wrap() {
+        for c in SAMPLES.map(Counter) {
+            assert_eq!(serial_lt(c, crate::increment(c)), Some(true));
+            assert_eq!(serial_gt(crate::increment(c), c), Some(true));
+            assert_eq!(serial_lt(crate::increment(c), c), Some(false));
+            assert_eq!(serial_lt(c, c), Some(false));
+            assert_eq!(serial_gt(c, c), Some(false));
+        }
+        assert_eq!(serial_lt(Counter::MAX, Counter(5)), Some(true));
+        assert_eq!(serial_lt(Counter(5), Counter::MAX), Some(false));
+    }
+
+    #[test]
+    fn test_undefined_at_half_range() {
+        assert_eq!(serial_lt(Counter(0), Counter(HALF_RANGE)), None);
+        assert_eq!(serial_lt(Counter(HALF_RANGE), Counter(0)), None);
+        assert_eq!(serial_gt(Counter(7), Counter(HALF_RANGE + 7)), None);
+        assert_eq!(serial_distance(Counter(7), Counter(HALF_RANGE + 7)), None);
+        assert_eq!(serial_lt(Counter(0), Counter(HALF_RANGE - 1)), Some(true));
+        assert_eq!(serial_lt(Counter(0), Counter(HALF_RANGE + 1)), Some(false));
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(serial_distance(Counter(10), Counter(15)), Some(5));
+        assert_eq!(serial_distance(Counter(15), Counter(10)), Some(-5));
+        assert_eq!(serial_distance(Counter::MAX, Counter(1)), Some(2));
+        assert_eq!(serial_distance(Counter(1), Counter::MAX), Some(-2));
+        for a in SAMPLES.map(Counter) {
+            for b in SAMPLES.map(Counter) {
+                match serial_distance(a, b) {
+                    Some(d) => {
+                        assert_eq!(crate::add(a, Counter(d as u32)), b);
+                        assert_eq!(serial_distance(b, a), Some(-d));
+                        assert_eq!(serial_lt(a, b), Some(d > 0));
+                    }
+                    None => assert_eq!(serial_distance(b, a), None),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,466 @@
+//! Signed up/down counters that may go below zero.
+//!
+//! [`SignedCounter`] is an `i32` counter for quantities such as inventory,
+//! where a negative count records a backorder. As in the crate root, each
+//! operation comes in wrapping, checked and saturating forms. Every
+//! operation returns a [`SignedStep`] whose `sign_changed` flag reports when
+//! the count crossed zero, i.e. went from negative to non-negative or back.
+
+use crate::contracts::contract;
+use hax_lib as hax;
+
+/// A signed counter value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SignedCounter(pub i32);
+
+impl SignedCounter {
+    /// The smallest counter value.
+    pub const MIN: SignedCounter = SignedCounter(i32::MIN);
+    /// The largest counter value.
+    pub const MAX: SignedCounter = SignedCounter(i32::MAX);
+
+    /// Whether the count is below zero.
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+}
+
+/// The result of a signed operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedStep {
+    /// The new counter value.
+    pub value: SignedCounter,
+    /// Whether the operation crossed zero: the input was negative and the
+    /// result is not, or the other way round.
+    pub sign_changed: bool,
+}
+
+/// Errors reported by the checked signed operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignedCounterError {
+    /// The result would exceed `SignedCounter::MAX`.
+    Overflow { c: SignedCounter, n: SignedCounter },
+    /// The result would go below `SignedCounter::MIN`.
+    Underflow { c: SignedCounter, n: SignedCounter },
+}
+
+contract! {
+    /// Whether going from `before` to `after` crosses zero.
+    ///
+    /// # Properties
+    /// - `sign_changed(c, c) == false`
+    /// - `sign_changed(a, b) == sign_changed(b, a)`
+    #[hax::lean::before("@[simp, spec]")]
+    #[hax::lean::after(
+        "-- Specification of sign_changed
+theorem Hax_basic.Signed.sign_changed_spec (before after : Hax_basic.Signed.SignedCounter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Signed.sign_changed before after)
+  ⦃ ⇓ result => ⌜ result = decide ((before._0 < 0) ≠ (after._0 < 0)) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Signed.sign_changed, Hax_basic.Signed.Impl.is_negative]
+  all_goals (simp_all; try grind)
+"
+    )]
+    ensures(|result| result == (before.is_negative() != after.is_negative()))
+    pub fn sign_changed(before: SignedCounter, after: SignedCounter) -> bool {
+        before.is_negative() != after.is_negative()
+    }
+}
+
+fn step(before: SignedCounter, after: SignedCounter) -> SignedStep {
+    SignedStep {
+        value: after,
+        sign_changed: sign_changed(before, after),
+    }
+}
+
+contract! {
+    /// Creates a new signed counter initialized to zero.
+    ///
+    /// # Properties
+    /// - `new_counter() == SignedCounter(0)`
+    #[hax::lean::before("@[simp, spec]")]
+    #[hax::lean::after(
+        "-- Specification of new_counter
+theorem Hax_basic.Signed.new_counter_spec :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Signed.new_counter Rust_primitives.Hax.Tuple0.mk)
+  ⦃ ⇓ result => ⌜ result._0 = 0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Signed.new_counter]
+"
+    )]
+    ensures(|result| result.0 == 0)
+    pub fn new_counter() -> SignedCounter {
+        SignedCounter(0)
+    }
+}
+
+contract! {
+    /// Resets the counter to zero.
+    ///
+    /// # Returns
+    /// Zero, with `sign_changed` set when `c` was negative.
+    ensures(|result| result.value.0 == 0 && result.sign_changed == c.is_negative())
+    pub fn reset(c: SignedCounter) -> SignedStep {
+        step(c, new_counter())
+    }
+}
+
+contract! {
+    /// Adds `n` to the counter, wrapping at `SignedCounter::MAX` and `SignedCounter::MIN`.
+    ///
+    /// # Properties
+    /// - `add(c, SignedCounter(0)).value == c`
+    /// - `add(SignedCounter(-1), SignedCounter(1)).sign_changed`
+    #[hax::lean::before("@[simp, spec]")]
+    #[hax::lean::after(
+        "-- Specification of add: addition modulo 2^32 in two's complement
+theorem Hax_basic.Signed.add_spec (c n : Hax_basic.Signed.SignedCounter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Signed.add c n)
+  ⦃ ⇓ result => ⌜ result.value._0 = c._0 + n._0
+      ∧ result.sign_changed = decide ((c._0 < 0) ≠ (result.value._0 < 0)) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Signed.add, Hax_basic.Signed.step, Core.Num.Impl_2.wrapping_add]
+  all_goals (simp_all; try grind)
+"
+    )]
+    ensures(|result| result.value.0 == c.0.wrapping_add(n.0)
+        && result.sign_changed == sign_changed(c, result.value))
+    pub fn add(c: SignedCounter, n: SignedCounter) -> SignedStep {
+        step(c, SignedCounter(c.0.wrapping_add(n.0)))
+    }
+}
+
+contract! {
+    /// Subtracts `n` from the counter, wrapping at `SignedCounter::MIN` and `SignedCounter::MAX`.
+    ///
+    /// # Properties
+    /// - `subtract(c, SignedCounter(0)).value == c`
+    /// - `subtract(SignedCounter(0), SignedCounter(1)).value == SignedCounter(-1)`
+    #[hax::lean::before("@[simp, spec]")]
+    #[hax::lean::after(
+        "-- Specification of subtract: subtraction modulo 2^32 in two's complement
+theorem Hax_basic.Signed.subtract_spec (c n : Hax_basic.Signed.SignedCounter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Signed.subtract c n)
+  ⦃ ⇓ result => ⌜ result.value._0 = c._0 - n._0
+      ∧ result.sign_changed = decide ((c._0 < 0) ≠ (result.value._0 < 0)) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Signed.subtract, Hax_basic.Signed.step, Core.Num.Impl_2.wrapping_sub]
+  all_goals (simp_all; try grind)
+"
+    )]
+    ensures(|result| result.value.0 == c.0.wrapping_sub(n.0)
+        && result.sign_changed == sign_changed(c, result.value))
+    pub fn subtract(c: SignedCounter, n: SignedCounter) -> SignedStep {
+        step(c, SignedCounter(c.0.wrapping_sub(n.0)))
+    }
+}
+
+contract! {
+    /// Increments the counter by one, wrapping at `SignedCounter::MAX`.
+    ///
+    /// # Properties
+    /// - `increment(c) == add(c, SignedCounter(1))`
+    #[hax::lean::before("@[simp, spec]")]
+    #[hax::lean::after(
+        "-- Specification of increment: add with n = 1
+theorem Hax_basic.Signed.increment_spec (c : Hax_basic.Signed.SignedCounter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Signed.increment c)
+  ⦃ ⇓ result => ⌜ result.value._0 = c._0 + 1
+      ∧ result.sign_changed = decide ((c._0 < 0) ≠ (result.value._0 < 0)) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Signed.increment, Hax_basic.Signed.add, Hax_basic.Signed.step,
+    Core.Num.Impl_2.wrapping_add]
+  all_goals (simp_all; try grind)
+"
+    )]
+    ensures(|result| result.value.0 == c.0.wrapping_add(1)
+        && result.sign_changed == sign_changed(c, result.value))
+    pub fn increment(c: SignedCounter) -> SignedStep {
+        add(c, SignedCounter(1))
+    }
+}
+
+contract! {
+    /// Decrements the counter by one, wrapping at `SignedCounter::MIN`.
+    ///
+    /// # Properties
+    /// - `decrement(c) == subtract(c, SignedCounter(1))`
+    /// - `decrement(new_counter())` goes to `-1` and reports `sign_changed`
+    #[hax::lean::before("@[simp, spec]")]
+    #[hax::lean::after(
+        "-- Specification of decrement: subtract with n = 1
+theorem Hax_basic.Signed.decrement_spec (c : Hax_basic.Signed.SignedCounter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Signed.decrement c)
+  ⦃ ⇓ result => ⌜ result.value._0 = c._0 - 1
+      ∧ result.sign_changed = decide ((c._0 < 0) ≠ (result.value._0 < 0)) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Signed.decrement, Hax_basic.Signed.subtract, Hax_basic.Signed.step,
+    Core.Num.Impl_2.wrapping_sub]
+  all_goals (simp_all; try grind)
+"
+    )]
+    ensures(|result| result.value.0 == c.0.wrapping_sub(1)
+        && result.sign_changed == sign_changed(c, result.value))
+    pub fn decrement(c: SignedCounter) -> SignedStep {
+        subtract(c, SignedCounter(1))
+    }
+}
+
+contract! {
+    /// Adds `n` to the counter, failing instead of wrapping.
+    ///
+    /// # Returns
+    /// The sum, or `Overflow`/`Underflow` when it falls outside the `i32` range.
+    #[hax::lean::after(
+        "-- Specification of checked_add: exact integer addition or an error
+theorem Hax_basic.Signed.checked_add_spec (c n : Hax_basic.Signed.SignedCounter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Signed.checked_add c n)
+  ⦃ ⇓ result => ⌜ match result with
+      | Core.Result.Result.Ok s => s.value._0.toInt = c._0.toInt + n._0.toInt
+      | Core.Result.Result.Err _ =>
+          c._0.toInt + n._0.toInt > 2147483647 ∨ c._0.toInt + n._0.toInt < -2147483648 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Signed.checked_add, Hax_basic.Signed.step]
+  all_goals (simp_all; try omega)
+"
+    )]
+    ensures(|result| match result {
+        Ok(s) => s.value.0 as i64 == c.0 as i64 + n.0 as i64
+            && s.sign_changed == sign_changed(c, s.value),
+        Err(_) => c.0 as i64 + n.0 as i64 > i32::MAX as i64
+            || (c.0 as i64 + n.0 as i64) < i32::MIN as i64,
+    })
+    pub fn checked_add(
+        c: SignedCounter,
+        n: SignedCounter,
+    ) -> Result<SignedStep, SignedCounterError> {
+        if n.0 >= 0 {
+            if c.0 <= i32::MAX - n.0 {
+                Ok(step(c, SignedCounter(c.0 + n.0)))
+            } else {
+                Err(SignedCounterError::Overflow { c, n })
+            }
+        } else if c.0 >= i32::MIN - n.0 {
+            Ok(step(c, SignedCounter(c.0 + n.0)))
+        } else {
+            Err(SignedCounterError::Underflow { c, n })
+        }
+    }
+}
+
+contract! {
+    /// Subtracts `n` from the counter, failing instead of wrapping.
+    ///
+    /// # Returns
+    /// The difference, or `Overflow`/`Underflow` when it falls outside the `i32` range.
+    #[hax::lean::after(
+        "-- Specification of checked_subtract: exact integer subtraction or an error
+theorem Hax_basic.Signed.checked_subtract_spec (c n : Hax_basic.Signed.SignedCounter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Signed.checked_subtract c n)
+  ⦃ ⇓ result => ⌜ match result with
+      | Core.Result.Result.Ok s => s.value._0.toInt = c._0.toInt - n._0.toInt
+      | Core.Result.Result.Err _ =>
+          c._0.toInt - n._0.toInt > 2147483647 ∨ c._0.toInt - n._0.toInt < -2147483648 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Signed.checked_subtract, Hax_basic.Signed.step]
+  all_goals (simp_all; try omega)
+"
+    )]
+    ensures(|result| match result {
+        Ok(s) => s.value.0 as i64 == c.0 as i64 - n.0 as i64
+            && s.sign_changed == sign_changed(c, s.value),
+        Err(_) => c.0 as i64 - n.0 as i64 > i32::MAX as i64
+            || (c.0 as i64 - n.0 as i64) < i32::MIN as i64,
+    })
+    pub fn checked_subtract(
+        c: SignedCounter,
+        n: SignedCounter,
+    ) -> Result<SignedStep, SignedCounterError> {
+        if n.0 >= 0 {
+            if c.0 >= i32::MIN + n.0 {
+                Ok(step(c, SignedCounter(c.0 - n.0)))
+            } else {
+                Err(SignedCounterError::Underflow { c, n })
+            }
+        } else if c.0 <= i32::MAX + n.0 {
+            Ok(step(c, SignedCounter(c.0 - n.0)))
+        } else {
+            Err(SignedCounterError::Overflow { c, n })
+        }
+    }
+}
+
+contract! {
+    /// Adds `n` to the counter, clamping at `SignedCounter::MIN` and `SignedCounter::MAX`.
+    #[hax::lean::after(
+        "-- Specification of saturating_add: the integer sum clamped to the i32 range
+theorem Hax_basic.Signed.saturating_add_spec (c n : Hax_basic.Signed.SignedCounter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Signed.saturating_add c n)
+  ⦃ ⇓ result => ⌜ result.value._0.toInt
+      = max (min (c._0.toInt + n._0.toInt) 2147483647) (-2147483648) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Signed.saturating_add, Hax_basic.Signed.step]
+  all_goals (simp_all; try omega)
+"
+    )]
+    ensures(|result| result.value.0 as i64
+        == (c.0 as i64 + n.0 as i64).clamp(i32::MIN as i64, i32::MAX as i64)
+        && result.sign_changed == sign_changed(c, result.value))
+    pub fn saturating_add(c: SignedCounter, n: SignedCounter) -> SignedStep {
+        if n.0 >= 0 {
+            if c.0 <= i32::MAX - n.0 {
+                step(c, SignedCounter(c.0 + n.0))
+            } else {
+                step(c, SignedCounter::MAX)
+            }
+        } else if c.0 >= i32::MIN - n.0 {
+            step(c, SignedCounter(c.0 + n.0))
+        } else {
+            step(c, SignedCounter::MIN)
+        }
+    }
+}
+
+contract! {
+    /// Subtracts `n` from the counter, clamping at `SignedCounter::MIN` and `SignedCounter::MAX`.
+    #[hax::lean::after(
+        "-- Specification of saturating_subtract: the integer difference clamped to the i32 range
+theorem Hax_basic.Signed.saturating_subtract_spec (c n : Hax_basic.Signed.SignedCounter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Signed.saturating_subtract c n)
+  ⦃ ⇓ result => ⌜ result.value._0.toInt
+      = max (min (c._0.toInt - n._0.toInt) 2147483647) (-2147483648) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Signed.saturating_subtract, Hax_basic.Signed.step]
+  all_goals (simp_all; try omega)
+"
+    )]
+    ensures(|result| result.value.0 as i64
+        == (c.0 as i64 - n.0 as i64).clamp(i32::MIN as i64, i32::MAX as i64)
+        && result.sign_changed == sign_changed(c, result.value))
+    pub fn saturating_subtract(c: SignedCounter, n: SignedCounter) -> SignedStep {
+        if n.0 >= 0 {
+            if c.0 >= i32::MIN + n.0 {
+                step(c, SignedCounter(c.0 - n.0))
+            } else {
+                step(c, SignedCounter::MIN)
+            }
+        } else if c.0 <= i32::MAX + n.0 {
+            step(c, SignedCounter(c.0 - n.0))
+        } else {
+            step(c, SignedCounter::MAX)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn s(value: i32) -> SignedCounter {
+        SignedCounter(value)
+    }
+
+    const SAMPLES: [i32; 9] = [
+        i32::MIN,
+        i32::MIN + 1,
+        -1000,
+        -1,
+        0,
+        1,
+        1000,
+        i32::MAX - 1,
+        i32::MAX,
+    ];
+
+    #[test]
+    fn test_backorders_cross_zero() {
+        let stock = new_counter();
+        let backorder = subtract(stock, s(3));
+        assert_eq!(backorder.value, s(-3));
+        assert!(backorder.sign_changed);
+        let partial = add(backorder.value, s(2));
+        assert_eq!(partial.value, s(-1));
+        assert!(!partial.sign_changed);
+        let restocked = increment(partial.value);
+        assert_eq!(restocked.value, s(0));
+        assert!(restocked.sign_changed);
+        assert!(!increment(restocked.value).sign_changed);
+        assert!(decrement(new_counter()).sign_changed);
+        assert!(reset(s(-5)).sign_changed);
+        assert!(!reset(s(5)).sign_changed);
+    }
+
+    #[test]
+    fn test_modes_match_i32() {
+        for a in SAMPLES {
+            for b in SAMPLES {
+                let (c, n) = (s(a), s(b));
+                assert_eq!(add(c, n).value, s(a.wrapping_add(b)));
+                assert_eq!(subtract(c, n).value, s(a.wrapping_sub(b)));
+                assert_eq!(checked_add(c, n).ok().map(|x| x.value.0), a.checked_add(b));
+                assert_eq!(
+                    checked_subtract(c, n).ok().map(|x| x.value.0),
+                    a.checked_sub(b)
+                );
+                assert_eq!(saturating_add(c, n).value, s(a.saturating_add(b)));
+                assert_eq!(saturating_subtract(c, n).value, s(a.saturating_sub(b)));
+                for r in [add(c, n), subtract(c, n), saturating_add(c, n)] {
+                    assert_eq!(r.sign_changed, (a < 0) != (r.value.0 < 0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_checked_errors() {
+        assert_eq!(
+            checked_add(SignedCounter::MAX, s(1)),
+            Err(SignedCounterError::Overflow {
+                c: SignedCounter::MAX,
+                n: s(1)
+            })
+        );
+        assert_eq!(
+            checked_add(SignedCounter::MIN, s(-1)),
+            Err(SignedCounterError::Underflow {
+                c: SignedCounter::MIN,
+                n: s(-1)
+            })
+        );
+        assert_eq!(
+            checked_subtract(SignedCounter::MIN, s(1)),
+            Err(SignedCounterError::Underflow {
+                c: SignedCounter::MIN,
+                n: s(1)
+            })
+        );
+        assert_eq!(
+            checked_subtract(s(0), SignedCounter::MIN),
+            Err(SignedCounterError::Overflow {
+                c: s(0),
+                n: SignedCounter::MIN
+            })
+        );
+    }
+
+    #[test]
+    fn test_wrapping_flips_sign() {
+        let wrapped = increment(SignedCounter::MAX);
+        assert_eq!(wrapped.value, SignedCounter::MIN);
+        assert!(wrapped.sign_changed);
+        let saturated = saturating_add(SignedCounter::MAX, s(1));
+        assert_eq!(saturated.value, SignedCounter::MAX);
+        assert!(!saturated.sign_changed);
+    }
+}
@@ -0,0 +1,231 @@
+//! Normalization of counter programs.
+//!
+//! Every program of [`Op`]s is equivalent to "optionally reset, then add a
+//! net delta": `Reset` forgets everything before it, and the remaining
+//! increments, decrements, adds and subtracts commute modulo 2^32. A
+//! [`NormalForm`] stores exactly that, so long event logs can be compacted
+//! into at most two operations.
+
+use crate::op::Op;
+use crate::Counter;
+use hax_lib as hax;
+
+/// A program in canonical form: an optional `Reset` followed by one `Add`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NormalForm {
+    /// Whether the program resets the counter before adding `delta`.
+    pub reset: bool,
+    /// The net amount added, modulo 2^32.
+    pub delta: Counter,
+}
+
+impl NormalForm {
+    /// The empty program.
+    pub const IDENTITY: NormalForm = NormalForm {
+        reset: false,
+        delta: Counter(0),
+    };
+
+    /// Extends the program with one more operation.
+    ///
+    /// # Properties
+    /// - `nf.push(op).apply(c) == op::apply(nf.apply(c), op)`
+    pub fn push(self, op: Op) -> NormalForm {
+        match op {
+            Op::Increment => NormalForm {
+                delta: crate::increment(self.delta),
+                ..self
+            },
+            Op::Decrement => NormalForm {
+                delta: crate::decrement(self.delta),
+                ..self
+            },
+            Op::Add(n) => NormalForm {
+                delta: crate::add(self.delta, n),
+                ..self
+            },
+            Op::Subtract(n) => NormalForm {
+                delta: crate::subtract(self.delta, n),
+                ..self
+            },
+            Op::Reset => NormalForm {
+                reset: true,
+                delta: crate::new_counter(),
+            },
+        }
+    }
+
+    /// Runs the normalized program starting from `c`.
+    ///
+    /// # Properties
+    /// - `NormalForm::IDENTITY.apply(c) == c`
+    /// - `nf.apply(c) == op::run(c, &nf.to_ops())`
+    pub fn apply(self, c: Counter) -> Counter {
+        let start = if self.reset { crate::reset(c) } else { c };
+        crate::add(start, self.delta)
+    }
+
+    /// The normalized program as a list of at most two operations.
+    ///
+    /// A zero `delta` is omitted, so the empty program normalizes to `[]`.
+    #[hax::exclude]
+    pub fn to_ops(self) -> Vec<Op> {
+        let mut ops = Vec::with_capacity(2);
+        if self.reset {
+            ops.push(Op::Reset);
+        }
+        if self.delta != Counter(0) {
+            ops.push(Op::Add(self.delta));
+        }
+        ops
+    }
+}
+
+/// Collapses a program into its normal form.
+///
+/// # Arguments
+/// * `ops` - The program to normalize
+///
+/// # Returns
+/// The `NormalForm` with `normalize(ops).apply(c) == op::run(c, ops)` for every `c`.
+///
+/// # Properties
+/// - `normalize(&[]) == NormalForm::IDENTITY`
+/// - `normalize(ops).apply(c) == op::run(c, ops)`
+#[hax::lean::after(
+    "-- Pure Lean model of a normal form
+def Hax_basic.Simplify.NormalForm.denote (nf : Hax_basic.Simplify.NormalForm) (c : u32) : u32 :=
+  (if nf.reset then 0 else c) + nf.delta._0
+
+-- Pushing an operation composes the model with that operation
+theorem Hax_basic.Simplify.NormalForm.denote_push
+  (nf : Hax_basic.Simplify.NormalForm) (op : Hax_basic.Op.Op) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Simplify.Impl.push nf op)
+  ⦃ ⇓ r => ⌜ ∀ c, r.denote c = Hax_basic.Op.denote op (nf.denote c) ⌝ ⦄
+  := by
+  cases op <;> mvcgen [Hax_basic.Simplify.Impl.push, Hax_basic.increment_eq,
+    Hax_basic.decrement_eq, Hax_basic.add_eq, Hax_basic.subtract_eq, Hax_basic.new_counter_eq,
+    Core.Num.Impl_8.wrapping_add, Core.Num.Impl_8.wrapping_sub]
+  all_goals (intro c; cases nf.reset <;> simp_all [Hax_basic.Simplify.NormalForm.denote,
+    Hax_basic.Op.denote] <;> grind)
+
+-- The normalized program computes the same result as the original, for every start value
+theorem Hax_basic.Simplify.normalize_spec (ops : RustSlice Hax_basic.Op.Op) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Simplify.normalize ops)
+  ⦃ ⇓ nf => ⌜ ∀ c, nf.denote c = ops.toList.foldl (fun c op => Hax_basic.Op.denote op c) c ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Simplify.normalize, Hax_basic.Simplify.NormalForm.denote_push]
+  case inv =>
+    exact ⇓ ⟨i, acc⟩ => ⌜ ∀ c, acc.denote c
+      = (ops.toList.take i).foldl (fun c op => Hax_basic.Op.denote op c) c ⌝
+  all_goals (simp_all [Hax_basic.Simplify.NormalForm.denote, List.take_succ, List.foldl_append])
+
+theorem Hax_basic.Simplify.normalize_run (ops : RustSlice Hax_basic.Op.Op) (c : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (do
+    let nf ← Hax_basic.Simplify.normalize ops
+    let a ← Hax_basic.Simplify.Impl.apply nf c
+    let b ← Hax_basic.Op.run c ops
+    pure (a, b))
+  ⦃ ⇓ r => ⌜ r.1 = r.2 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Simplify.normalize_spec, Hax_basic.Op.run_spec, Hax_basic.Simplify.Impl.apply,
+    Hax_basic.reset_eq, Hax_basic.add_eq, Core.Num.Impl_8.wrapping_add]
+  all_goals (simp_all [Hax_basic.Simplify.NormalForm.denote]; try grind)
+"
+)]
+// `normalize_spec` proves this loop with an invariant on its `fold_range`.
+#[allow(clippy::needless_range_loop)]
+pub fn normalize(ops: &[Op]) -> NormalForm {
+    let mut nf = NormalForm::IDENTITY;
+    for i in 0..ops.len() {
+        nf = nf.push(ops[i]);
+    }
+    nf
+}
+
+/// Rewrites a program into an equivalent program of at most two operations.
+///
+/// # Properties
+/// - `op::run(c, &simplify(ops)) == op::run(c, ops)` for every `c`
+/// - `simplify(&simplify(ops)) == simplify(ops)`
+#[hax::exclude]
+pub fn simplify(ops: &[Op]) -> Vec<Op> {
+    normalize(ops).to_ops()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::op;
+
+    const STARTS: [Counter; 4] = [Counter(0), Counter(1), Counter(1000), Counter::MAX];
+
+    fn assert_equivalent(ops: &[Op]) {
+        let simplified = simplify(ops);
+        assert!(simplified.len() <= 2);
+        for c in STARTS {
+            assert_eq!(normalize(ops).apply(c), op::run(c, ops));
+            assert_eq!(op::run(c, &simplified), op::run(c, ops));
+        }
+    }
+
+    #[test]
+    fn test_empty_program() {
+        assert_eq!(normalize(&[]), NormalForm::IDENTITY);
+        assert_eq!(simplify(&[]), vec![]);
+    }
+
+    #[test]
+    fn test_increments_collapse_to_single_add() {
+        let ops = vec![Op::Increment; 1000];
+        assert_eq!(simplify(&ops), vec![Op::Add(Counter(1000))]);
+        assert_equivalent(&ops);
+    }
+
+    #[test]
+    fn test_reset_discards_prefix() {
+        let ops = [
+            Op::Add(Counter(7)),
+            Op::Reset,
+            Op::Increment,
+            Op::Subtract(Counter(3)),
+        ];
+        assert_eq!(
+            simplify(&ops),
+            vec![Op::Reset, Op::Add(crate::subtract(Counter(1), Counter(3)))]
+        );
+        assert_equivalent(&ops);
+        assert_eq!(simplify(&[Op::Add(Counter(9)), Op::Reset]), vec![Op::Reset]);
+    }
+
+    #[test]
+    fn test_cancelling_ops_vanish() {
+        let ops = [Op::Add(Counter(5)), Op::Decrement, Op::Subtract(Counter(4))];
+        assert_eq!(simplify(&ops), vec![]);
+        assert_equivalent(&ops);
+    }
+
+    #[test]
+    fn test_mixed_programs_are_equivalent() {
+        let alphabet = [
+            Op::Increment,
+            Op::Decrement,
+            Op::Add(Counter(u32::MAX - 2)),
+            Op::Subtract(Counter(17)),
+            Op::Reset,
+        ];
+        // Every program of length 3 over the alphabet.
+        for a in alphabet {
+            for b in alphabet {
+                for c in alphabet {
+                    let ops = [a, b, c];
+                    assert_equivalent(&ops);
+                    assert_eq!(simplify(&simplify(&ops)), simplify(&ops));
+                }
+            }
+        }
+    }
+}
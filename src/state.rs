@@ -0,0 +1,156 @@
+//! A mutable counter with an optional bounded history.
+//!
+//! `CounterState` is a convenience layer for callers that want method syntax
+//! and an audit trail. Every update is computed by the pure functions in the
+//! crate root, which remain the verified source of truth, so this module is
+//! excluded from extraction.
+
+use std::collections::VecDeque;
+
+use crate::op::{self, Op};
+use crate::Counter;
+use hax_lib as hax;
+
+/// One recorded update: the value before it and the operation applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// The counter value before `op` was applied.
+    pub previous: Counter,
+    /// The operation that replaced `previous`.
+    pub op: Op,
+}
+
+/// A counter value updated in place through `&mut self` methods.
+///
+/// When created with [`CounterState::with_history`], the most recent
+/// `capacity` updates are kept, oldest first; older entries are dropped.
+#[hax::exclude]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CounterState {
+    value: Counter,
+    history: VecDeque<HistoryEntry>,
+    capacity: usize,
+}
+
+#[hax::exclude]
+impl CounterState {
+    /// Creates a counter at `new_counter()` that keeps no history.
+    pub fn new() -> Self {
+        Self::with_history(0)
+    }
+
+    /// Creates a counter at `new_counter()` that remembers its last
+    /// `capacity` updates.
+    pub fn with_history(capacity: usize) -> Self {
+        CounterState {
+            value: crate::new_counter(),
+            history: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// The current counter value.
+    pub fn value(&self) -> Counter {
+        self.value
+    }
+
+    /// The recorded updates, oldest first.
+    pub fn history(&self) -> impl ExactSizeIterator<Item = &HistoryEntry> + '_ {
+        self.history.iter()
+    }
+
+    /// Applies `increment` and returns the new value.
+    pub fn increment(&mut self) -> Counter {
+        self.update(Op::Increment)
+    }
+
+    /// Applies `decrement` and returns the new value.
+    pub fn decrement(&mut self) -> Counter {
+        self.update(Op::Decrement)
+    }
+
+    /// Applies `add(_, n)` and returns the new value.
+    pub fn add(&mut self, n: Counter) -> Counter {
+        self.update(Op::Add(n))
+    }
+
+    /// Applies `subtract(_, n)` and returns the new value.
+    pub fn subtract(&mut self, n: Counter) -> Counter {
+        self.update(Op::Subtract(n))
+    }
+
+    /// Applies `reset` and returns the new value.
+    pub fn reset(&mut self) -> Counter {
+        self.update(Op::Reset)
+    }
+
+    fn update(&mut self, op: Op) -> Counter {
+        let next = op::apply(self.value, op);
+        if self.capacity > 0 {
+            if self.history.len() == self.capacity {
+                self.history.pop_front();
+            }
+            self.history.push_back(HistoryEntry {
+                previous: self.value,
+                op,
+            });
+        }
+        self.value = next;
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_methods_match_pure_functions() {
+        let mut s = CounterState::new();
+        assert_eq!(s.value(), crate::new_counter());
+        assert_eq!(s.increment(), Counter(1));
+        assert_eq!(s.add(Counter(10)), Counter(11));
+        assert_eq!(s.subtract(Counter(3)), Counter(8));
+        assert_eq!(s.decrement(), Counter(7));
+        assert_eq!(s.reset(), Counter(0));
+        assert_eq!(s.decrement(), Counter::MAX);
+        assert_eq!(s.history().len(), 0);
+    }
+
+    #[test]
+    fn test_history_records_previous_value_and_op() {
+        let mut s = CounterState::with_history(8);
+        s.increment();
+        s.add(Counter(5));
+        s.reset();
+        let entries: Vec<_> = s.history().copied().collect();
+        assert_eq!(
+            entries,
+            vec![
+                HistoryEntry {
+                    previous: Counter(0),
+                    op: Op::Increment
+                },
+                HistoryEntry {
+                    previous: Counter(1),
+                    op: Op::Add(Counter(5))
+                },
+                HistoryEntry {
+                    previous: Counter(6),
+                    op: Op::Reset
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut s = CounterState::with_history(2);
+        for _ in 0..5 {
+            s.increment();
+        }
+        let previous: Vec<_> = s.history().map(|e| e.previous).collect();
+        assert_eq!(previous, vec![Counter(3), Counter(4)]);
+        assert_eq!(s.value(), Counter(5));
+    }
+}
@@ -0,0 +1,345 @@
+//! Counters tagged with a unit of measure.
+//!
+//! A [`TypedCounter<U>`] is a [`Counter`] carrying a zero-sized unit marker
+//! `U`, so requests, bytes and errors cannot be added to one another by
+//! accident. Arithmetic only accepts operands of the same unit and delegates
+//! to the verified functions in the crate root; changing unit is explicit,
+//! through conversion functions with documented rounding.
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
+use crate::contracts::contract;
+use crate::Counter;
+use hax_lib as hax;
+
+/// A unit of measure for [`TypedCounter`].
+///
+/// Units are zero-sized marker types; new units can be declared outside this
+/// crate by implementing this trait on an empty struct.
+pub trait Unit: Copy + Eq + fmt::Debug {
+    /// Name of the unit, used when displaying a counter.
+    const NAME: &'static str;
+}
+
+macro_rules! units {
+    ($($(#[$meta:meta])* $unit:ident => $name:literal,)*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            pub struct $unit;
+
+            impl Unit for $unit {
+                const NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+units! {
+    /// Counts handled requests.
+    Requests => "requests",
+    /// Counts bytes.
+    Bytes => "bytes",
+    /// Counts kilobytes of 1024 bytes.
+    Kilobytes => "kilobytes",
+    /// Counts errors.
+    Errors => "errors",
+}
+
+/// How a conversion to a coarser unit rounds a partial unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Drop the partial unit, so `1023` bytes is `0` kilobytes.
+    Down,
+    /// Count the partial unit as a whole one, so `1` byte is `1` kilobyte.
+    Up,
+    /// Round to the closest whole unit, with halves rounding up.
+    Nearest,
+}
+
+/// A counter value measured in unit `U`.
+///
+/// Mixing units is a type error:
+///
+/// ```compile_fail
+/// use hax_basic::typed::{Bytes, Requests, TypedCounter};
+///
+/// let requests = TypedCounter::<Requests>::new();
+/// let bytes = TypedCounter::<Bytes>::new();
+/// let _ = requests.add(bytes);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TypedCounter<U> {
+    value: Counter,
+    unit: PhantomData<U>,
+}
+
+impl<U: Unit> TypedCounter<U> {
+    contract! {
+        /// Creates a counter of unit `U` initialized to zero.
+        ensures(|result| result.value == crate::new_counter())
+        pub fn new() -> TypedCounter<U> {
+            TypedCounter::from_counter(crate::new_counter())
+        }
+    }
+
+    /// Tags an untyped counter with unit `U`.
+    pub fn from_counter(value: Counter) -> TypedCounter<U> {
+        TypedCounter {
+            value,
+            unit: PhantomData,
+        }
+    }
+
+    /// The untyped counter value.
+    pub fn value(self) -> Counter {
+        self.value
+    }
+
+    contract! {
+        /// Increments the counter by one unit.
+        ensures(|result| result.value == crate::increment(self.value))
+        pub fn increment(self) -> TypedCounter<U> {
+            TypedCounter::from_counter(crate::increment(self.value))
+        }
+    }
+
+    contract! {
+        /// Decrements the counter by one unit.
+        ensures(|result| result.value == crate::decrement(self.value))
+        pub fn decrement(self) -> TypedCounter<U> {
+            TypedCounter::from_counter(crate::decrement(self.value))
+        }
+    }
+
+    contract! {
+        /// Adds a counter of the same unit.
+        ///
+        /// # Properties
+        /// - `a.add(b).value() == add(a.value(), b.value())`
+        // `add_spec` is stated about this method; `Add` and `AddAssign`
+        // below forward to it.
+        #[allow(clippy::should_implement_trait)]
+        #[hax::lean::after(
+            "-- add on typed counters is add on the underlying counters
+theorem Hax_basic.Typed.Impl.add_spec (U : Type) [Hax_basic.Typed.Unit U]
+  (a n : Hax_basic.Typed.TypedCounter U) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Typed.Impl.add U a n)
+  ⦃ ⇓ result => ⌜ result.value._0 = a.value._0 + n.value._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Typed.Impl.add, Hax_basic.Typed.Impl.from_counter]
+"
+        )]
+        ensures(|result| result.value == crate::add(self.value, n.value))
+        pub fn add(self, n: TypedCounter<U>) -> TypedCounter<U> {
+            TypedCounter::from_counter(crate::add(self.value, n.value))
+        }
+    }
+
+    contract! {
+        /// Subtracts a counter of the same unit.
+        ///
+        /// # Properties
+        /// - `a.subtract(b).value() == subtract(a.value(), b.value())`
+        #[hax::lean::after(
+            "-- subtract on typed counters is subtract on the underlying counters
+theorem Hax_basic.Typed.Impl.subtract_spec (U : Type) [Hax_basic.Typed.Unit U]
+  (a n : Hax_basic.Typed.TypedCounter U) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Typed.Impl.subtract U a n)
+  ⦃ ⇓ result => ⌜ result.value._0 = a.value._0 - n.value._0 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Typed.Impl.subtract, Hax_basic.Typed.Impl.from_counter]
+"
+        )]
+        ensures(|result| result.value == crate::subtract(self.value, n.value))
+        pub fn subtract(self, n: TypedCounter<U>) -> TypedCounter<U> {
+            TypedCounter::from_counter(crate::subtract(self.value, n.value))
+        }
+    }
+
+    contract! {
+        /// Resets the counter to zero units.
+        ensures(|result| result.value == crate::reset(self.value))
+        pub fn reset(self) -> TypedCounter<U> {
+            TypedCounter::from_counter(crate::reset(self.value))
+        }
+    }
+}
+
+/// Number of bytes in a kilobyte.
+pub const BYTES_PER_KILOBYTE: u32 = 1024;
+
+contract! {
+    /// Converts bytes to kilobytes, rounding a partial kilobyte as `rounding` says.
+    ///
+    /// The result always fits: even `Counter::MAX` bytes rounded up is only
+    /// `2^22` kilobytes.
+    ///
+    /// # Properties
+    /// - `bytes_to_kilobytes(kilobytes_to_bytes(k)?, r) == k` for every `r`
+    // Spelled-out division extracts to plain arithmetic the Lean proof can use.
+    #[allow(clippy::manual_div_ceil)]
+    ensures(|result| match rounding {
+        Rounding::Down => result.value.0 == bytes.value.0 / BYTES_PER_KILOBYTE,
+        Rounding::Up => {
+            result.value.0 as u64
+                == (bytes.value.0 as u64 + BYTES_PER_KILOBYTE as u64 - 1)
+                    / BYTES_PER_KILOBYTE as u64
+        }
+        Rounding::Nearest => {
+            result.value.0 as u64
+                == (bytes.value.0 as u64 + BYTES_PER_KILOBYTE as u64 / 2)
+                    / BYTES_PER_KILOBYTE as u64
+        }
+    })
+    pub fn bytes_to_kilobytes(
+        bytes: TypedCounter<Bytes>,
+        rounding: Rounding,
+    ) -> TypedCounter<Kilobytes> {
+        let b = bytes.value.0 as u64;
+        let per = BYTES_PER_KILOBYTE as u64;
+        let kb = match rounding {
+            Rounding::Down => b / per,
+            Rounding::Up => (b + per - 1) / per,
+            Rounding::Nearest => (b + per / 2) / per,
+        };
+        TypedCounter::from_counter(Counter(kb as u32))
+    }
+}
+
+contract! {
+    /// Converts kilobytes to bytes.
+    ///
+    /// # Returns
+    /// `None` when the byte count does not fit in a `Counter`, i.e. for more than
+    /// `Counter::MAX / BYTES_PER_KILOBYTE` kilobytes.
+    ensures(|result| match result {
+        Some(b) => b.value.0 as u64 == kilobytes.value.0 as u64 * BYTES_PER_KILOBYTE as u64,
+        None => kilobytes.value.0 as u64 * BYTES_PER_KILOBYTE as u64 > u32::MAX as u64,
+    })
+    pub fn kilobytes_to_bytes(kilobytes: TypedCounter<Kilobytes>) -> Option<TypedCounter<Bytes>> {
+        if kilobytes.value.0 <= u32::MAX / BYTES_PER_KILOBYTE {
+            Some(TypedCounter::from_counter(Counter(
+                kilobytes.value.0 * BYTES_PER_KILOBYTE,
+            )))
+        } else {
+            None
+        }
+    }
+}
+
+/// Wrapping addition, identical to [`TypedCounter::add`].
+impl<U: Unit> Add for TypedCounter<U> {
+    type Output = TypedCounter<U>;
+
+    fn add(self, n: TypedCounter<U>) -> TypedCounter<U> {
+        TypedCounter::add(self, n)
+    }
+}
+
+/// Wrapping subtraction, identical to [`TypedCounter::subtract`].
+impl<U: Unit> Sub for TypedCounter<U> {
+    type Output = TypedCounter<U>;
+
+    fn sub(self, n: TypedCounter<U>) -> TypedCounter<U> {
+        TypedCounter::subtract(self, n)
+    }
+}
+
+impl<U: Unit> AddAssign for TypedCounter<U> {
+    fn add_assign(&mut self, n: TypedCounter<U>) {
+        *self = TypedCounter::add(*self, n);
+    }
+}
+
+impl<U: Unit> SubAssign for TypedCounter<U> {
+    fn sub_assign(&mut self, n: TypedCounter<U>) {
+        *self = TypedCounter::subtract(*self, n);
+    }
+}
+
+#[hax::exclude]
+impl<U: Unit> fmt::Display for TypedCounter<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.value, U::NAME)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(n: u32) -> TypedCounter<Bytes> {
+        TypedCounter::from_counter(Counter(n))
+    }
+
+    fn kilobytes(n: u32) -> TypedCounter<Kilobytes> {
+        TypedCounter::from_counter(Counter(n))
+    }
+
+    #[test]
+    fn test_operations_delegate_to_core() {
+        let a = TypedCounter::<Requests>::from_counter(Counter(u32::MAX - 1));
+        let b = TypedCounter::<Requests>::from_counter(Counter(5));
+        assert_eq!(a.add(b).value(), crate::add(a.value(), b.value()));
+        assert_eq!(b.subtract(a).value(), crate::subtract(b.value(), a.value()));
+        assert_eq!(a.increment().value(), crate::increment(a.value()));
+        assert_eq!(a.decrement().value(), crate::decrement(a.value()));
+        assert_eq!(a.reset(), TypedCounter::new());
+        assert_eq!(a + b, a.add(b));
+        assert_eq!(b - a, b.subtract(a));
+        let mut c = a;
+        c += b;
+        c -= b;
+        assert_eq!(c, a);
+    }
+
+    #[test]
+    fn test_bytes_to_kilobytes_rounding() {
+        for (b, down, up, nearest) in [
+            (0, 0, 0, 0),
+            (1, 0, 1, 0),
+            (511, 0, 1, 0),
+            (512, 0, 1, 1),
+            (1024, 1, 1, 1),
+            (1536, 1, 2, 2),
+            (u32::MAX, 4194303, 4194304, 4194304),
+        ] {
+            assert_eq!(
+                bytes_to_kilobytes(bytes(b), Rounding::Down),
+                kilobytes(down)
+            );
+            assert_eq!(bytes_to_kilobytes(bytes(b), Rounding::Up), kilobytes(up));
+            assert_eq!(
+                bytes_to_kilobytes(bytes(b), Rounding::Nearest),
+                kilobytes(nearest)
+            );
+        }
+    }
+
+    #[test]
+    fn test_kilobytes_to_bytes() {
+        assert_eq!(kilobytes_to_bytes(kilobytes(3)), Some(bytes(3072)));
+        assert_eq!(
+            kilobytes_to_bytes(kilobytes(4194303)),
+            Some(bytes(u32::MAX - 1023))
+        );
+        assert_eq!(kilobytes_to_bytes(kilobytes(4194304)), None);
+        for k in [0, 1, 77, 4194303] {
+            let b = kilobytes_to_bytes(kilobytes(k)).unwrap();
+            for rounding in [Rounding::Down, Rounding::Up, Rounding::Nearest] {
+                assert_eq!(bytes_to_kilobytes(b, rounding), kilobytes(k));
+            }
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(bytes(42).to_string(), "42 bytes");
+        assert_eq!(TypedCounter::<Errors>::new().to_string(), "0 errors");
+    }
+}
@@ -0,0 +1,334 @@
+//! Extended-precision counters built from `Counter` limbs.
+//!
+//! A [`WideCounter<N>`] stores a `32 * N`-bit value as `N` little-endian
+//! [`Counter`] limbs. Addition propagates carries limb by limb through
+//! [`crate::overflowing_add`], so every step is an operation of the verified
+//! core; the Lean proofs show that limb-wise addition is big-integer addition
+//! modulo `2^(32 * N)`.
+
+use core::cmp::Ordering;
+use core::ops::{Add, AddAssign};
+
+use crate::contracts::contract;
+use crate::Counter;
+use hax_lib as hax;
+
+/// A 256-bit counter.
+pub type Counter256 = WideCounter<8>;
+
+/// A `32 * N`-bit counter made of `N` limbs, least significant first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WideCounter<const N: usize> {
+    limbs: [Counter; N],
+}
+
+impl<const N: usize> WideCounter<N> {
+    /// Number of bits in the counter.
+    pub const BITS: u32 = 32 * N as u32;
+
+    /// Creates a counter with every limb at `new_counter()`.
+    pub fn new() -> WideCounter<N> {
+        WideCounter {
+            limbs: [crate::new_counter(); N],
+        }
+    }
+
+    /// Creates a counter from its limbs, least significant first.
+    pub fn from_limbs(limbs: [Counter; N]) -> WideCounter<N> {
+        WideCounter { limbs }
+    }
+
+    /// Creates a counter whose value is `c`.
+    pub fn from_counter(c: Counter) -> WideCounter<N> {
+        let mut limbs = [crate::new_counter(); N];
+        if N > 0 {
+            limbs[0] = c;
+        }
+        WideCounter { limbs }
+    }
+
+    /// The limbs, least significant first.
+    pub fn limbs(self) -> [Counter; N] {
+        self.limbs
+    }
+
+    /// Adds `n`, reporting the carry out of the most significant limb.
+    ///
+    /// # Returns
+    /// `(self + n mod 2^BITS, carry)`, where `carry` is `true` when the true
+    /// sum does not fit in `BITS` bits.
+    #[hax::lean::after(
+        "-- Big-integer value of a little-endian list of limbs
+def Hax_basic.Wide.value : List Hax_basic.Counter → Nat
+  | [] => 0
+  | l :: ls => l._0.toNat + 4294967296 * Hax_basic.Wide.value ls
+
+theorem Hax_basic.Wide.value_append (ls ms : List Hax_basic.Counter) :
+  Hax_basic.Wide.value (ls ++ ms)
+    = Hax_basic.Wide.value ls + 2 ^ (32 * ls.length) * Hax_basic.Wide.value ms := by
+  induction ls with
+  | nil => simp [Hax_basic.Wide.value]
+  | cons l ls ih => simp [Hax_basic.Wide.value, ih, Nat.mul_add, Nat.mul_succ]; grind
+
+theorem Hax_basic.Wide.value_lt (ls : List Hax_basic.Counter) :
+  Hax_basic.Wide.value ls < 2 ^ (32 * ls.length) := by
+  induction ls with
+  | nil => simp [Hax_basic.Wide.value]
+  | cons l ls ih =>
+    have := l._0.toNat_lt
+    simp [Hax_basic.Wide.value, Nat.mul_succ, Nat.pow_add]
+    grind
+
+-- Limb-wise addition with carry is big-integer addition
+theorem Hax_basic.Wide.Impl.overflowing_add_spec (N : usize)
+  (a n : Hax_basic.Wide.WideCounter N) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Wide.Impl.overflowing_add N a n)
+  ⦃ ⇓ r => ⌜ Hax_basic.Wide.value r.1.limbs.toList + (if r.2 then 2 ^ (32 * N.toNat) else 0)
+      = Hax_basic.Wide.value a.limbs.toList + Hax_basic.Wide.value n.limbs.toList ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Wide.Impl.overflowing_add, Hax_basic.overflowing_add_spec]
+  case inv =>
+    exact ⇓ ⟨i, limbs, carry⟩ => ⌜
+      (∀ j, i ≤ j → j < N.toNat → limbs.toList[j]! = a.limbs.toList[j]!) ∧
+      Hax_basic.Wide.value (limbs.toList.take i) + (if carry then 2 ^ (32 * i) else 0)
+        = Hax_basic.Wide.value (a.limbs.toList.take i)
+          + Hax_basic.Wide.value (n.limbs.toList.take i) ⌝
+  all_goals (simp_all [List.take_succ, Hax_basic.Wide.value_append, Hax_basic.Wide.value,
+    Nat.pow_succ]; try omega)
+"
+    )]
+    // `overflowing_add_spec` proves this loop with an invariant on the carry.
+    #[allow(clippy::needless_range_loop)]
+    pub fn overflowing_add(self, n: WideCounter<N>) -> (WideCounter<N>, bool) {
+        let mut limbs = self.limbs;
+        let mut carry = false;
+        for i in 0..N {
+            let (sum, c1) = crate::overflowing_add(self.limbs[i], n.limbs[i]);
+            let carry_in = if carry { Counter(1) } else { Counter(0) };
+            let (sum, c2) = crate::overflowing_add(sum, carry_in);
+            limbs[i] = sum;
+            carry = c1 || c2;
+        }
+        (WideCounter { limbs }, carry)
+    }
+
+    /// Adds `n`, wrapping at `2^BITS`.
+    ///
+    /// # Properties
+    /// - `a.add(WideCounter::new()) == a`
+    /// - `a.add(b) == b.add(a)`
+    #[hax::lean::after(
+        "-- add is big-integer addition modulo 2^(32 * N)
+theorem Hax_basic.Wide.Impl.add_spec (N : usize) (a n : Hax_basic.Wide.WideCounter N) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Wide.Impl.add N a n)
+  ⦃ ⇓ r => ⌜ Hax_basic.Wide.value r.limbs.toList
+      = (Hax_basic.Wide.value a.limbs.toList + Hax_basic.Wide.value n.limbs.toList)
+        % 2 ^ (32 * N.toNat) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Wide.Impl.add, Hax_basic.Wide.Impl.overflowing_add_spec]
+  all_goals (split <;> simp_all [Nat.add_mod_right]; try omega)
+"
+    )]
+    // `increment_spec` is proved from this method's `add_spec`; the `Add`
+    // impl below forwards to it.
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(self, n: WideCounter<N>) -> WideCounter<N> {
+        self.overflowing_add(n).0
+    }
+
+    /// Adds a single `Counter`, wrapping at `2^BITS`.
+    pub fn add_counter(self, n: Counter) -> WideCounter<N> {
+        self.add(WideCounter::from_counter(n))
+    }
+
+    contract! {
+        /// Increments the counter by one, wrapping at `2^BITS`.
+        ///
+        /// # Properties
+        /// - `a.increment() == a.add_counter(1)`
+        /// - `n` successive increments equal `add_counter(n)`
+        #[hax::lean::after(
+            "-- increment adds one modulo 2^(32 * N)
+theorem Hax_basic.Wide.Impl.increment_spec (N : usize) (a : Hax_basic.Wide.WideCounter N)
+  (h : 0 < N.toNat) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Wide.Impl.increment N a)
+  ⦃ ⇓ r => ⌜ Hax_basic.Wide.value r.limbs.toList
+      = (Hax_basic.Wide.value a.limbs.toList + 1) % 2 ^ (32 * N.toNat) ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Wide.Impl.increment, Hax_basic.Wide.Impl.add_counter,
+    Hax_basic.Wide.Impl.from_counter, Hax_basic.Wide.Impl.add_spec]
+  all_goals (simp_all [Hax_basic.Wide.value]; try omega)
+
+-- Law: k successive increments add k, mirroring `add_eq_increments` for Counter
+theorem Hax_basic.Wide.Impl.increments_eq_add (N : usize) (a : Hax_basic.Wide.WideCounter N)
+  (h : 0 < N.toNat) (k : Nat) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Nat.repeat (· >>= Hax_basic.Wide.Impl.increment N) k (pure a))
+  ⦃ ⇓ r => ⌜ Hax_basic.Wide.value r.limbs.toList
+      = (Hax_basic.Wide.value a.limbs.toList + k) % 2 ^ (32 * N.toNat) ⌝ ⦄
+  := by
+  induction k with
+  | zero =>
+    have := Hax_basic.Wide.value_lt a.limbs.toList
+    mvcgen [Nat.repeat]
+    simp_all [Nat.mod_eq_of_lt]
+  | succ k ih =>
+    simp only [Nat.repeat]
+    mvcgen [ih, Hax_basic.Wide.Impl.increment_spec N _ h]
+    all_goals (simp_all [Nat.add_mod]; try omega)
+"
+        )]
+        ensures(|result| result == self.add_counter(Counter(1)))
+        pub fn increment(self) -> WideCounter<N> {
+            self.add_counter(Counter(1))
+        }
+    }
+
+    /// Resets every limb to zero.
+    pub fn reset(self) -> WideCounter<N> {
+        WideCounter::new()
+    }
+}
+
+impl<const N: usize> Default for WideCounter<N> {
+    fn default() -> WideCounter<N> {
+        WideCounter::new()
+    }
+}
+
+/// Wrapping addition, identical to [`WideCounter::add`].
+impl<const N: usize> Add for WideCounter<N> {
+    type Output = WideCounter<N>;
+
+    fn add(self, n: WideCounter<N>) -> WideCounter<N> {
+        WideCounter::add(self, n)
+    }
+}
+
+impl<const N: usize> AddAssign for WideCounter<N> {
+    fn add_assign(&mut self, n: WideCounter<N>) {
+        *self = WideCounter::add(*self, n);
+    }
+}
+
+/// Compares by numeric value, most significant limb first.
+#[hax::exclude]
+impl<const N: usize> Ord for WideCounter<N> {
+    fn cmp(&self, other: &WideCounter<N>) -> Ordering {
+        self.limbs.iter().rev().cmp(other.limbs.iter().rev())
+    }
+}
+
+#[hax::exclude]
+impl<const N: usize> PartialOrd for WideCounter<N> {
+    fn partial_cmp(&self, other: &WideCounter<N>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Counter128 = WideCounter<4>;
+
+    fn from_u128(v: u128) -> Counter128 {
+        WideCounter::from_limbs([0, 1, 2, 3].map(|i| Counter((v >> (32 * i)) as u32)))
+    }
+
+    fn to_u128(w: Counter128) -> u128 {
+        w.limbs()
+            .iter()
+            .rev()
+            .fold(0, |acc, limb| (acc << 32) | limb.0 as u128)
+    }
+
+    const SAMPLES: [u128; 7] = [
+        0,
+        1,
+        u32::MAX as u128,
+        1 << 32,
+        u64::MAX as u128,
+        u128::MAX - 1,
+        u128::MAX,
+    ];
+
+    #[test]
+    fn test_add_matches_u128() {
+        for a in SAMPLES {
+            for b in SAMPLES {
+                let (sum, carry) = from_u128(a).overflowing_add(from_u128(b));
+                assert_eq!((to_u128(sum), carry), a.overflowing_add(b));
+                assert_eq!(to_u128(from_u128(a).add(from_u128(b))), a.wrapping_add(b));
+                assert_eq!(from_u128(a) + from_u128(b), from_u128(a).add(from_u128(b)));
+                let mut w = from_u128(a);
+                w += from_u128(b);
+                assert_eq!(to_u128(w), a.wrapping_add(b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_increment_carries_across_limbs() {
+        let a = from_u128(u64::MAX as u128);
+        let w = a.increment();
+        assert_eq!(w.limbs(), [Counter(0), Counter(0), Counter(1), Counter(0)]);
+        assert_eq!(to_u128(w), 1 << 64);
+        assert_eq!(from_u128(u128::MAX).increment(), Counter128::new());
+        let wide = Counter256::from_limbs([Counter::MAX; 8]).increment();
+        assert_eq!(wide, Counter256::new());
+    }
+
+    #[test]
+    fn test_add_counter_is_repeated_increment() {
+        let start = from_u128((1 << 64) - 5);
+        let mut w = start;
+        for _ in 0..10 {
+            w = w.increment();
+        }
+        assert_eq!(w, start.add_counter(Counter(10)));
+        assert_eq!(to_u128(w), (1 << 64) + 5);
+    }
+
+    #[test]
+    fn test_ordering_is_numeric() {
+        for a in SAMPLES {
+            for b in SAMPLES {
+                assert_eq!(from_u128(a).cmp(&from_u128(b)), a.cmp(&b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_wider_than_128_bits() {
+        let half = Counter256::from_limbs([
+            Counter::MAX,
+            Counter::MAX,
+            Counter::MAX,
+            Counter::MAX,
+            Counter(0),
+            Counter(0),
+            Counter(0),
+            Counter(0),
+        ]);
+        let w = half.add(half).add_counter(Counter(2));
+        assert_eq!(Counter256::BITS, 256);
+        assert_eq!(
+            w.limbs(),
+            [
+                Counter(0),
+                Counter(0),
+                Counter(0),
+                Counter(0),
+                Counter(2),
+                Counter(0),
+                Counter(0),
+                Counter(0)
+            ]
+        );
+        assert_eq!(w.reset(), Counter256::default());
+    }
+}
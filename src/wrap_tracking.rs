@@ -0,0 +1,254 @@
+//! A counter that remembers how many times it wrapped.
+//!
+//! [`WrapTrackingCounter`] pairs a [`Counter`] with an epoch that is bumped on
+//! every carry out of [`crate::overflowing_add`] and dropped on every borrow
+//! out of [`crate::overflowing_subtract`]. Together they reconstruct a logical
+//! 64-bit total, `epoch * 2^32 + value`, from the 32-bit verified core.
+
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
+use crate::contracts::contract;
+use crate::Counter;
+use hax_lib as hax;
+
+/// A counter value together with the number of times it has wrapped.
+///
+/// The epoch itself wraps after `2^32` wraps, so [`WrapTrackingCounter::total`]
+/// is exact until the logical total reaches `2^64`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WrapTrackingCounter {
+    epoch: u32,
+    value: Counter,
+}
+
+impl WrapTrackingCounter {
+    /// Creates a counter at zero in epoch zero.
+    pub fn new() -> WrapTrackingCounter {
+        WrapTrackingCounter {
+            epoch: 0,
+            value: crate::new_counter(),
+        }
+    }
+
+    /// The wrapped 32-bit counter value.
+    pub fn value(self) -> Counter {
+        self.value
+    }
+
+    /// How many times the value has wrapped past `Counter::MAX`, net of
+    /// borrows.
+    pub fn epoch(self) -> u32 {
+        self.epoch
+    }
+
+    /// The logical total `epoch * 2^32 + value`.
+    pub fn total(self) -> u64 {
+        self.epoch as u64 * (1 << 32) + self.value.0 as u64
+    }
+
+    contract! {
+        /// Adds `n`, moving to the next epoch if the value wraps.
+        ///
+        /// # Properties
+        /// - `w.add(n).value() == add(w.value(), n)`
+        /// - `w.add(n).total() == w.total().wrapping_add(n)`
+        // `add_all_spec` inducts over this method through `add_spec`; the
+        // `Add<Counter>` impl below forwards to it.
+        #[allow(clippy::should_implement_trait)]
+        #[hax::lean::after(
+            "-- Pure Lean model of the logical total
+def Hax_basic.Wrap_tracking.WrapTrackingCounter.total
+  (w : Hax_basic.Wrap_tracking.WrapTrackingCounter) : Nat :=
+  w.epoch.toNat * 4294967296 + w.value._0.toNat
+
+-- add advances the logical total by n, modulo 2^64
+theorem Hax_basic.Wrap_tracking.Impl.add_spec
+  (w : Hax_basic.Wrap_tracking.WrapTrackingCounter) (n : Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Wrap_tracking.Impl.add w n)
+  ⦃ ⇓ r => ⌜ r.total = (w.total + n._0.toNat) % 2 ^ 64 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Wrap_tracking.Impl.add, Hax_basic.overflowing_add_spec,
+    Core.Num.Impl_8.wrapping_add]
+  all_goals (simp_all [Hax_basic.Wrap_tracking.WrapTrackingCounter.total, UInt32.toNat_add];
+    try omega)
+"
+        )]
+        ensures(|result| result.value == crate::add(self.value, n)
+            && result.total() == self.total().wrapping_add(n.0 as u64))
+        pub fn add(self, n: Counter) -> WrapTrackingCounter {
+            let (value, carry) = crate::overflowing_add(self.value, n);
+            let epoch = if carry {
+                self.epoch.wrapping_add(1)
+            } else {
+                self.epoch
+            };
+            WrapTrackingCounter { epoch, value }
+        }
+    }
+
+    contract! {
+        /// Subtracts `n`, moving back an epoch if the value wraps.
+        ///
+        /// # Properties
+        /// - `w.subtract(n).value() == subtract(w.value(), n)`
+        /// - `w.subtract(n).total() == w.total().wrapping_sub(n)`
+        ensures(|result| result.value == crate::subtract(self.value, n)
+            && result.total() == self.total().wrapping_sub(n.0 as u64))
+        pub fn subtract(self, n: Counter) -> WrapTrackingCounter {
+            let (value, borrow) = crate::overflowing_subtract(self.value, n);
+            let epoch = if borrow {
+                self.epoch.wrapping_sub(1)
+            } else {
+                self.epoch
+            };
+            WrapTrackingCounter { epoch, value }
+        }
+    }
+
+    /// Adds every value in `ns`, left to right.
+    ///
+    /// # Properties
+    /// - `w.add_all(ns).total() == w.total() + Σns` (mod 2^64)
+    /// - starting from `new()`, `total()` is exactly `Σns` while that is below 2^64
+    #[hax::lean::after(
+        "-- add_all advances the logical total by the sum of all additions, modulo 2^64
+theorem Hax_basic.Wrap_tracking.Impl.add_all_spec
+  (w : Hax_basic.Wrap_tracking.WrapTrackingCounter) (ns : RustSlice Hax_basic.Counter) :
+  ⦃ ⌜ True ⌝ ⦄
+  (Hax_basic.Wrap_tracking.Impl.add_all w ns)
+  ⦃ ⇓ r => ⌜ r.total = (w.total + (ns.toList.map (·._0.toNat)).sum) % 2 ^ 64 ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Wrap_tracking.Impl.add_all, Hax_basic.Wrap_tracking.Impl.add_spec]
+  case inv =>
+    exact ⇓ ⟨i, acc⟩ => ⌜ acc.total
+      = (w.total + ((ns.toList.take i).map (·._0.toNat)).sum) % 2 ^ 64 ⌝
+  all_goals (simp_all [List.take_succ, Nat.add_mod, Nat.add_assoc]; try omega)
+
+-- Without epoch overflow, epoch * 2^32 + value is exactly the sum of all additions
+theorem Hax_basic.Wrap_tracking.Impl.add_all_exact (ns : RustSlice Hax_basic.Counter)
+  (h : (ns.toList.map (·._0.toNat)).sum < 2 ^ 64) :
+  ⦃ ⌜ True ⌝ ⦄
+  (do
+    let w ← Hax_basic.Wrap_tracking.Impl.new Rust_primitives.Hax.Tuple0.mk
+    Hax_basic.Wrap_tracking.Impl.add_all w ns)
+  ⦃ ⇓ r => ⌜ r.epoch.toNat * 4294967296 + r.value._0.toNat
+      = (ns.toList.map (·._0.toNat)).sum ⌝ ⦄
+  := by
+  mvcgen [Hax_basic.Wrap_tracking.Impl.new, Hax_basic.new_counter_eq,
+    Hax_basic.Wrap_tracking.Impl.add_all_spec]
+  all_goals (simp_all [Hax_basic.Wrap_tracking.WrapTrackingCounter.total, Nat.mod_eq_of_lt h])
+"
+    )]
+    // `add_all_spec` proves this loop with an invariant over the prefix of `ns`.
+    #[allow(clippy::needless_range_loop)]
+    pub fn add_all(self, ns: &[Counter]) -> WrapTrackingCounter {
+        let mut w = self;
+        for i in 0..ns.len() {
+            w = w.add(ns[i]);
+        }
+        w
+    }
+}
+
+/// Adds a `Counter`, identical to [`WrapTrackingCounter::add`].
+impl Add<Counter> for WrapTrackingCounter {
+    type Output = WrapTrackingCounter;
+
+    fn add(self, n: Counter) -> WrapTrackingCounter {
+        WrapTrackingCounter::add(self, n)
+    }
+}
+
+/// Subtracts a `Counter`, identical to [`WrapTrackingCounter::subtract`].
+impl Sub<Counter> for WrapTrackingCounter {
+    type Output = WrapTrackingCounter;
+
+    fn sub(self, n: Counter) -> WrapTrackingCounter {
+        WrapTrackingCounter::subtract(self, n)
+    }
+}
+
+impl AddAssign<Counter> for WrapTrackingCounter {
+    fn add_assign(&mut self, n: Counter) {
+        *self = WrapTrackingCounter::add(*self, n);
+    }
+}
+
+impl SubAssign<Counter> for WrapTrackingCounter {
+    fn sub_assign(&mut self, n: Counter) {
+        *self = WrapTrackingCounter::subtract(*self, n);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: [u32; 6] = [0, 1, 7, 1 << 31, u32::MAX - 1, u32::MAX];
+
+    #[test]
+    fn test_add_tracks_carries() {
+        let w = WrapTrackingCounter::new().add(Counter::MAX);
+        assert_eq!((w.epoch(), w.value()), (0, Counter::MAX));
+        let w = w.add(Counter(1));
+        assert_eq!((w.epoch(), w.value()), (1, Counter(0)));
+        assert_eq!(w.total(), 1 << 32);
+        let w = w.add(Counter::MAX).add(Counter(2));
+        assert_eq!((w.epoch(), w.value()), (2, Counter(1)));
+    }
+
+    #[test]
+    fn test_subtract_borrows_from_epoch() {
+        let w = WrapTrackingCounter::new().add(Counter::MAX).add(Counter(5));
+        let w = w.subtract(Counter(6));
+        assert_eq!((w.epoch(), w.value()), (0, Counter(u32::MAX - 1)));
+        assert_eq!(w.total(), u32::MAX as u64 - 1);
+        let w = WrapTrackingCounter::new().subtract(Counter(1));
+        assert_eq!(w.total(), u64::MAX);
+    }
+
+    #[test]
+    fn test_operators_match_methods() {
+        let w = WrapTrackingCounter::new().add(Counter::MAX);
+        assert_eq!(w + Counter(3), w.add(Counter(3)));
+        assert_eq!(w - Counter(u32::MAX), w.subtract(Counter(u32::MAX)));
+        let mut v = w;
+        v += Counter(3);
+        assert_eq!(v.epoch(), 1);
+        v -= Counter(3);
+        assert_eq!(v, w);
+    }
+
+    #[test]
+    fn test_total_is_sum_of_additions() {
+        let ns: Vec<Counter> = SAMPLES
+            .iter()
+            .cycle()
+            .take(1000)
+            .map(|&n| Counter(n))
+            .collect();
+        let expected: u64 = ns.iter().map(|n| n.0 as u64).sum();
+        let w = WrapTrackingCounter::new().add_all(&ns);
+        assert_eq!(w.total(), expected);
+        assert_eq!(
+            w.value(),
+            ns.iter().fold(Counter(0), |c, &n| crate::add(c, n))
+        );
+    }
+
+    #[test]
+    fn test_total_matches_u64_arithmetic() {
+        for start in SAMPLES {
+            let w = WrapTrackingCounter::new()
+                .add(Counter(start))
+                .add(Counter::MAX);
+            for n in SAMPLES {
+                let n = Counter(n);
+                assert_eq!(w.add(n).total(), w.total().wrapping_add(n.0 as u64));
+                assert_eq!(w.subtract(n).total(), w.total().wrapping_sub(n.0 as u64));
+                assert_eq!(w.add(n).subtract(n), w);
+            }
+        }
+    }
+}